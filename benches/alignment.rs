@@ -0,0 +1,86 @@
+//! Moves the input around for every sample, to tell a real difference between implementations apart from a lucky layout
+//! (see the README's "Benchmarks Disclaimer"): `day13_impls` always finds the input at the same address,
+//! with the same stack above it, so a faster result there may come from where things happened to land.
+//!
+//! Before each sample, the input is copied into a fresh buffer at a random offset (0 to 4 KiB, so every alignment up to a page),
+//! behind a random-sized allocation that shifts the implementation's own allocations,
+//! and the sample runs a random number of stack frames deeper, which moves the stack like a larger environment block would
+//! (the environment sits above `main`'s stack, so its size shifts everything on it).
+//! That's only an approximation: the environment itself is the same for every sample, see the README for varying it.
+//! Every implementation sees the same layouts in the same order.
+//!
+//! The samples of `Align_A/<impl>/<input>` then spread over the layouts;
+//! `bench_report spread --group Align_A` shows how far, next to `bench_report spread` for `Day13_A`'s fixed layout.
+//! Two implementations whose spreads overlap here aren't told apart by a `Day13_A` win alone.
+
+use std::time::Instant;
+
+use criterion::{
+    black_box, criterion_group, criterion_main, Bencher, BenchmarkId, Criterion, Throughput,
+};
+use day13_compare::generator::{Preset, Rng};
+
+#[path = "../tests/support/benched.rs"]
+mod benched;
+use benched::benched;
+
+/// The input's offset in its buffer, and the size of the allocation before it, are below this
+const MAX_OFFSET: usize = 4096;
+/// Each sample runs up to this many [deeper] frames down the stack
+const MAX_STACK_FRAMES: usize = 256;
+/// The least each [deeper] frame takes on the stack
+const STACK_STEP: usize = 16;
+
+/// Calls `f` `frames` stack frames deeper than the caller
+#[inline(never)]
+fn deeper<R>(frames: usize, f: impl FnOnce() -> R) -> R {
+    let pad = black_box([0u8; STACK_STEP]);
+    if frames == 0 {
+        return f();
+    }
+    let result = deeper(frames - 1, f);
+    black_box(pad);
+    result
+}
+
+/// Runs `day13` on `input`, placed anew for each sample
+fn perturbed(b: &mut Bencher, input: &str, day13: impl Fn(&str) -> usize) {
+    // the same seed for every implementation, so they're measured on the same layouts
+    let mut rng = Rng::new(13);
+    b.iter_custom(|iters| {
+        let heap_pad = black_box(vec![0u8; rng.below(MAX_OFFSET) + 1]);
+        let offset = rng.below(MAX_OFFSET);
+        let mut buffer = Vec::with_capacity(offset + input.len());
+        buffer.resize(offset, 0);
+        buffer.extend_from_slice(input.as_bytes());
+        let placed = std::str::from_utf8(&buffer[offset..]).unwrap();
+
+        let elapsed = deeper(rng.below(MAX_STACK_FRAMES), || {
+            let start = Instant::now();
+            for _ in 0..iters {
+                black_box(day13(black_box(placed)));
+            }
+            start.elapsed()
+        });
+        drop(heap_pad);
+        elapsed
+    });
+}
+
+fn bench_alignment(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Align_A");
+    for preset in Preset::BENCH {
+        let input = &preset.generate();
+        let name = preset.bench_name();
+        group.throughput(Throughput::Bytes(input.len() as u64));
+        for (impl_name, day13) in benched() {
+            group.bench_with_input(BenchmarkId::new(impl_name, name), input, |b, i| {
+                perturbed(b, i, day13)
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_alignment);
+criterion_main!(benches);
@@ -0,0 +1,82 @@
+//! A separate measurement pass next to `day13_impls`: counts what each implementation allocates on each of its inputs,
+//! with a counting global allocator (which would skew the timings if `day13_impls` installed it).
+//! Writes one table per metric, in the TSV format `bench_report tsv` produces,
+//! so memory plots with `scripts/thrpt_bar_chart.gp` just like throughput does.
+//!
+//! usage: [DAY13_ALLOC_DIR=<output dir>] cargo bench --bench allocations    (default: target/allocations)
+//! The directory comes from the environment, as `cargo bench -- <filter>` passes the filter to every bench target.
+
+#[path = "../tests/support/benched.rs"]
+mod benched;
+#[path = "../tests/support/counting_alloc.rs"]
+mod counting_alloc;
+#[path = "../tests/support/resources.rs"]
+mod resources;
+
+use benched::benched;
+use counting_alloc::{measure, AllocStats, CountingAlloc};
+use day13_compare::generator::Preset;
+use std::{fs, io::Write, path::PathBuf};
+
+#[global_allocator]
+static ALLOC: CountingAlloc = CountingAlloc;
+
+type Metric = fn(&AllocStats) -> usize;
+
+/// file name, unit, and how to get it from the stats
+const METRICS: [(&str, &str, Metric); 3] = [
+    ("alloc_count", "allocations", |stats| stats.allocations),
+    ("alloc_bytes", "bytes", |stats| stats.bytes),
+    ("alloc_peak_live", "bytes", |stats| stats.peak_live),
+];
+
+/// The same inputs as `day13_impls`
+fn inputs() -> Vec<(String, String)> {
+    let mut inputs: Vec<_> = Preset::BENCH
+        .iter()
+        .map(|preset| (preset.bench_name().to_owned(), preset.generate()))
+        .collect();
+    for resource in resources::resources().unwrap() {
+        if !resource.meta.invalid() {
+            inputs.push((resource.name, fs::read_to_string(&resource.path).unwrap()));
+        }
+    }
+    inputs
+}
+
+fn main() {
+    let dir = std::env::var_os("DAY13_ALLOC_DIR")
+        .map_or_else(|| PathBuf::from("target/allocations"), PathBuf::from);
+    fs::create_dir_all(&dir).unwrap();
+
+    let inputs = inputs();
+    let stats: Vec<Vec<AllocStats>> = inputs
+        .iter()
+        .map(|(_, input)| {
+            benched()
+                .map(|(_, day13)| measure(|| day13(input)).1)
+                .collect()
+        })
+        .collect();
+
+    for (file_name, unit, metric) in METRICS {
+        let path = dir.join(format!("{file_name}.data"));
+        let mut out = fs::File::create(&path).unwrap();
+        let header: Vec<_> = benched()
+            .map(|(name, _)| format!("low\t{name}\thigh"))
+            .collect();
+        writeln!(out, "Title\t{}", header.join("\t")).unwrap();
+        for ((name, _), stats) in inputs.iter().zip(&stats) {
+            // exact counts, so low = mid = high
+            let values: Vec<_> = stats
+                .iter()
+                .map(|stats| {
+                    let value = metric(stats);
+                    format!("{value}\t{value}\t{value}")
+                })
+                .collect();
+            writeln!(out, "{name} ({unit})\t{}", values.join("\t")).unwrap();
+        }
+        println!("wrote {}", path.display());
+    }
+}
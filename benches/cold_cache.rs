@@ -0,0 +1,136 @@
+//! Rotates through many distinct generated inputs, laid out over a buffer larger than the last-level cache
+//! and visited in a random order, with the pairs shuffled between them.
+//! Unlike `day13_impls`, which loops on one input, each run then starts with cold caches and a branch predictor
+//! that hasn't seen the input before (see the README's "Benchmarks Disclaimer").
+//!
+//! `Cold_A/<impl>/cold` is the rotation, and `Cold_A/<impl>/hot` runs the same loop on just one of the inputs, for comparison.
+//! The buffer is 256 MiB by default; set `DAY13_COLD_BUFFER_MIB` to match (well, exceed) your machine's cache.
+
+use std::{
+    ops::Range,
+    time::{Duration, Instant},
+};
+
+use criterion::{
+    black_box, criterion_group, criterion_main, Bencher, BenchmarkId, Criterion, Throughput,
+};
+use day13_compare::generator::{Params, Placement, Preset, Rng};
+
+#[path = "../tests/support/benched.rs"]
+mod benched;
+use benched::benched;
+
+const DEFAULT_BUFFER_MIB: usize = 256;
+const PAIRS_PER_INPUT: usize = 16;
+
+fn buffer_bytes() -> usize {
+    let mib = match std::env::var("DAY13_COLD_BUFFER_MIB") {
+        Ok(mib) => mib
+            .parse()
+            .expect("DAY13_COLD_BUFFER_MIB should be a number of MiB"),
+        Err(_) => DEFAULT_BUFFER_MIB,
+    };
+    mib * 1024 * 1024
+}
+
+/// Alternates between a couple of shapes, so the pairs next to each other don't look alike
+fn shapes() -> [Params; 2] {
+    let aoc_like = Preset::AocLike.params().unwrap();
+    let deeper = Params {
+        depth: 1..=10,
+        number_len: 1..=6,
+        shared_prefix_len: 24,
+        first_difference: Placement::Last,
+        equal_percent: 5,
+        ..aoc_like.clone()
+    };
+    [aoc_like, deeper]
+}
+
+/// One buffer of at least `bytes`, holding inputs of [PAIRS_PER_INPUT] shuffled pairs,
+/// and the spans of those inputs in the order they should be visited.
+fn cold_inputs(bytes: usize) -> (String, Vec<Range<usize>>) {
+    let mut rng = Rng::new(13);
+    let mut chunks = Vec::new();
+    let mut generated = 0;
+    for (seed, params) in (0..).zip(shapes().into_iter().cycle()) {
+        if generated >= bytes {
+            break;
+        }
+        let chunk = Params {
+            seed,
+            pairs: 1000,
+            ..params
+        }
+        .generate();
+        generated += chunk.len();
+        chunks.push(chunk);
+    }
+    let mut pairs: Vec<&str> = chunks
+        .iter()
+        .flat_map(|chunk| chunk.split("\n\n"))
+        .collect();
+    rng.shuffle(&mut pairs);
+
+    let mut buffer = String::with_capacity(generated);
+    let mut spans = Vec::with_capacity(pairs.len() / PAIRS_PER_INPUT + 1);
+    for input in pairs.chunks(PAIRS_PER_INPUT) {
+        let start = buffer.len();
+        for (idx, pair) in input.iter().enumerate() {
+            if idx > 0 {
+                buffer.push_str("\n\n");
+            }
+            buffer.push_str(pair);
+        }
+        spans.push(start..buffer.len());
+        buffer.push_str("\n\n");
+    }
+    rng.shuffle(&mut spans);
+    (buffer, spans)
+}
+
+/// Runs `day13` on each of `inputs` in turn, continuing where the previous sample stopped
+fn rotate(b: &mut Bencher, inputs: &[&str], day13: impl Fn(&str) -> usize) {
+    let mut next = 0;
+    b.iter_custom(|iters| {
+        let start = Instant::now();
+        for _ in 0..iters {
+            black_box(day13(black_box(inputs[next])));
+            next += 1;
+            if next == inputs.len() {
+                next = 0;
+            }
+        }
+        start.elapsed()
+    });
+}
+
+fn bench_cold_cache(c: &mut Criterion) {
+    let (buffer, spans) = cold_inputs(buffer_bytes());
+    let cold: Vec<&str> = spans.iter().map(|span| &buffer[span.clone()]).collect();
+    let hot = [cold[0]];
+    let cold_bytes = spans.iter().map(|span| span.len()).sum::<usize>() / spans.len();
+
+    let mut group = c.benchmark_group("Cold_A");
+    for (name, inputs, bytes) in [
+        ("cold", &cold[..], cold_bytes),
+        ("hot", &hot[..], hot[0].len()),
+    ] {
+        // the mean input length, every input is visited (about) as often as the others
+        group.throughput(Throughput::Bytes(bytes as u64));
+        for (impl_name, day13) in benched() {
+            group.bench_with_input(BenchmarkId::new(impl_name, name), inputs, |b, i| {
+                rotate(b, i, day13)
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group! {
+    name = benches;
+    // a sample should visit a good share of the inputs
+    config = Criterion::default().measurement_time(Duration::from_secs(10));
+    targets = bench_cold_cache
+}
+criterion_main!(benches);
@@ -2,83 +2,72 @@
 
 use std::{
     borrow::{Borrow, Cow},
+    panic,
     path::Path,
     rc::Rc,
     str::FromStr,
 };
 
 use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
-use day13_compare::{
-    input_handling_baseline, logos_lex, manual_lex, naive, naive_slice, prefix_comp_then_logos_lex,
-    single_pass_prefix_comp_then_logos_lex, SAMPLE,
-};
-use duplicate::duplicate;
+use day13_compare::{generator::Preset, registry::IMPLEMENTATIONS};
+
+#[path = "../tests/support/benched.rs"]
+mod benched;
+#[path = "../tests/support/resources.rs"]
+mod resources;
+use benched::{benched, Day13};
+use resources::{is_sidecar, Meta};
 
 struct TestData<'a> {
     name: Cow<'a, str>,
     input_fn: Box<dyn Fn() -> Option<Cow<'static, str>>>,
+    /// from the input's sidecar, if it has one
+    expected: Option<usize>,
+}
+
+/// Runs every implementation in the registry once on `input`, and describes each one that disagrees with `expected`,
+/// or with the first implementation (the reference) if there's no `expected`, by benchmark name.
+/// `input_handling_baseline` isn't checked, as it doesn't really compare the lines.
+/// `Err` if the reference itself panics, as there's nothing to compare with.
+fn disagreements(
+    input: &str,
+    expected: Option<usize>,
+) -> Result<Vec<(&'static str, String)>, String> {
+    let run = |day13: Day13| panic::catch_unwind(|| day13(input)).ok();
+    // the implementations that don't validate their input may panic, which is reported like any other disagreement
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+    let (reference, expected) = match expected {
+        Some(expected) => ("the sidecar", Some(expected)),
+        None => (IMPLEMENTATIONS[0].name, run(IMPLEMENTATIONS[0].day13)),
+    };
+    let disagreements = match expected {
+        None => Err(format!("the reference ({reference}) panicked")),
+        Some(expected) => Ok(IMPLEMENTATIONS
+            .iter()
+            .filter_map(|implementation| match run(implementation.day13) {
+                Some(answer) if answer == expected => None,
+                Some(answer) => Some((
+                    implementation.name,
+                    format!("returned {answer}, expected {expected} (from {reference})"),
+                )),
+                None => Some((
+                    implementation.name,
+                    format!("panicked, expected {expected} (from {reference})"),
+                )),
+            })
+            .collect()),
+    };
+    panic::set_hook(hook);
+    disagreements
 }
 
 fn bench_day13_impls(c: &mut Criterion) {
-    let data_set = vec![
-        TestData {
-            name: "original sample".into(),
-            input_fn: Box::new(|| Some(SAMPLE.into())),
-        },
-        TestData {
-            name: "orig sample repeated 1K".into(),
-            input_fn: Box::new(|| {
-                let mut base = SAMPLE.to_string();
-                base.push_str("\n\n");
-                base = base.repeat(1000);
-                base.truncate(base.len() - 2);
-                Some(base.into())
-            }),
-        },
-        TestData {
-            name: "single 10kB number, last digit different".into(),
-            input_fn: Box::new(|| {
-                const S: &str = "1029637485";
-                const N: usize = 1000;
-                let mut base = S.repeat(N);
-                {
-                    let base = unsafe { base.as_bytes_mut() };
-                    base[0] = b'[';
-                    base[base.len() - 2] = b']';
-                    base[base.len() - 1] = b'\n';
-                }
-                base = base.repeat(2);
-                base.truncate(base.len() - 2);
-                let last_of_first = base.pop().unwrap();
-                base.push(if last_of_first == '1' { '0' } else { '1' });
-                base.push(']');
-                // [12345]\n[12341]
-                Some(base.into())
-            }),
-        },
-        TestData {
-            name: "single 10kB number, first digit different".into(),
-            input_fn: Box::new(|| {
-                const S: &str = "1029637485";
-                const N: usize = 1000;
-                let mut base = S.repeat(N);
-                {
-                    // [12345]n[12345]
-                    let base = unsafe { base.as_bytes_mut() };
-                    base[0] = b'[';
-                    base[base.len() - 2] = b']';
-                    base[base.len() - 1] = b'\n';
-                }
-                base = base.repeat(2);
-                base.pop();
-                unsafe {
-                    base.as_bytes_mut()[1] = b'5';
-                }
-                // [12345]n[02345]
-                Some(base.into())
-            }),
-        },
-    ];
+    let data_set = Preset::BENCH.map(|preset| TestData {
+        name: preset.bench_name().into(),
+        input_fn: Box::new(move || Some(preset.generate().into())),
+        expected: None,
+    });
     fn for_each_file<'fs>(dir: &'fs Path) -> Vec<TestData<'fs>> {
         fn from_file(path: &Path) -> Option<String> {
             match std::fs::read_to_string(path) {
@@ -116,27 +105,43 @@ fn bench_day13_impls(c: &mut Criterion) {
                 }
                 .and_then(|dir_entry| {
                     let path = dir_entry.path();
-                    if path.is_file() {
-                        Some(TestData {
-                            name: path
-                                .as_path()
-                                .file_name()
-                                .unwrap()
-                                .to_string_lossy()
-                                .into_owned()
-                                .into(),
-                            input_fn: Box::new(move || from_file(path.as_path()).map(Cow::Owned)),
-                        })
-                    } else {
-                        None
+                    if !path.is_file() || is_sidecar(&path) {
+                        return None;
+                    }
+                    let meta = match Meta::for_input(&path) {
+                        Ok(meta) => meta,
+                        Err(e) => {
+                            eprintln!("{e}");
+                            return None;
+                        }
+                    };
+                    if meta.invalid() {
+                        // the validating implementations reject it, the rest may panic; `tests/golden.rs` covers it
+                        return None;
                     }
+                    Some(TestData {
+                        name: path
+                            .as_path()
+                            .file_name()
+                            .unwrap()
+                            .to_string_lossy()
+                            .into_owned()
+                            .into(),
+                        input_fn: Box::new(move || from_file(path.as_path()).map(Cow::Owned)),
+                        expected: meta.answer,
+                    })
                 })
             })
             .collect()
     }
 
+    let mut failures = Vec::new();
     let mut group = c.benchmark_group("Day13_A");
-    for TestData { name, input_fn } in data_set
+    for TestData {
+        name,
+        input_fn,
+        expected,
+    } in data_set
         .iter()
         .chain(for_each_file(Path::new("./benches/resources/")).iter())
     {
@@ -146,22 +151,38 @@ fn bench_day13_impls(c: &mut Criterion) {
         };
         group.throughput(Throughput::Bytes(input.len() as u64));
 
-        duplicate! {
-            [
-                module_name; [naive::pooled]; [naive::no_pool]; [naive_slice::pooled]; [naive_slice::no_pool]; [manual_lex]; [logos_lex]; [input_handling_baseline]; [single_pass_prefix_comp_then_logos_lex];
-            ]
-            group.bench_with_input(BenchmarkId::new(stringify!(module_name), name), input, |b, i| {
-                b.iter(|| module_name::day13(i))
-            });
-        }
-        duplicate! {
-            [chunk_size; [16]; [128]]
-            group.bench_with_input(BenchmarkId::new(concat!("prefix_comp_then_logos_lex", chunk_size), name), input, |b, i| {
-                b.iter(|| prefix_comp_then_logos_lex::day13::<chunk_size>(i))
+        // a wrong answer isn't worth timing
+        let wrong = match disagreements(input, *expected) {
+            Ok(wrong) => wrong,
+            Err(failure) => {
+                failures.push(format!("{name}: {failure}, nothing was benchmarked"));
+                continue;
+            }
+        };
+        failures.extend(
+            wrong
+                .iter()
+                .map(|(impl_name, failure)| format!("{name}: {impl_name} {failure}")),
+        );
+
+        for (impl_name, day13) in benched() {
+            if wrong.iter().any(|&(wrong_name, _)| wrong_name == impl_name) {
+                continue;
+            }
+            group.bench_with_input(BenchmarkId::new(impl_name, name), input, |b, i| {
+                b.iter(|| day13(i))
             });
         }
     }
     group.finish();
+
+    if !failures.is_empty() {
+        eprintln!("error: the implementations disagreed on some inputs, and the ones in the wrong weren't benchmarked on them:");
+        for failure in failures {
+            eprintln!("  {failure}");
+        }
+        std::process::exit(1);
+    }
 }
 
 criterion_group!(benches, bench_day13_impls);
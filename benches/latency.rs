@@ -0,0 +1,228 @@
+//! Per-pair latency, for callers that compare one pair per request, where the slow pairs matter more than the throughput.
+//! criterion times whole inputs, so this times each pair on its own instead: every implementation's `day13` is called on
+//! one pair at a time of a large generated input, a few rounds over it, and each call goes into that implementation's histogram.
+//! The implementations that keep pools (or arenas) between pairs are called through their `day13_thread_local`,
+//! so the pools stay warm from one call to the next, as they would for such a caller, instead of being built anew per pair.
+//!
+//! The input is [Preset::AocLike]-shaped pairs, with the pairs of the adversarial presets (the 10 kB numbers, deep nesting
+//! and long shared prefixes) mixed in at random, so their cost shows up in the tail like it would among common short pairs.
+//! Prints p50, p99, p99.9 and the max of each implementation, with the input its slowest pair came from,
+//! and writes the histograms and percentiles in the TSV format `bench_report tsv` produces, for `scripts/thrpt_bar_chart.gp`.
+//!
+//! usage: [DAY13_LATENCY_DIR=<output dir>] cargo bench --bench latency    (default: target/latency)
+//! Each time includes a call to `Instant::now`, whose own cost is printed for reference.
+
+#[path = "../tests/support/benched.rs"]
+mod benched;
+
+use benched::{benched, Day13};
+use day13_compare::{
+    generator::{Params, Preset, Rng},
+    naive, naive_slice,
+};
+use std::{fs, hint::black_box, io::Write, path::PathBuf, time::Instant};
+
+/// The variant of the implementation `name` that keeps its pools (or arenas) in thread locals,
+/// for those that have any
+fn warm(name: &str) -> Option<Day13> {
+    Some(match name {
+        "naive::pooled" => naive::pooled::day13_thread_local,
+        "naive::guarded" => naive::guarded::day13_thread_local,
+        "naive::arena" => naive::arena::day13_thread_local,
+        "naive_slice::pooled" => naive_slice::pooled::day13_thread_local,
+        "naive_slice::arena" => naive_slice::arena::day13_thread_local,
+        _ => return None,
+    })
+}
+
+/// The common pairs, which the adversarial ones are mixed into
+const COMMON_PAIRS: usize = 20_000;
+/// The adversarial presets, each of whose pairs is mixed in this many times
+const ADVERSARIAL: [Preset; 4] = [
+    Preset::LongNumberLastDigit,
+    Preset::LongNumberFirstDigit,
+    Preset::DeepNesting,
+    Preset::LongSharedPrefix,
+];
+const ADVERSARIAL_REPEATS: usize = 2;
+/// Passes over the input, after one to warm up
+const ROUNDS: usize = 5;
+/// The percentiles printed and written, as (name, fraction)
+const PERCENTILES: [(&str, f64); 3] = [("p50", 0.5), ("p99", 0.99), ("p99.9", 0.999)];
+
+/// A pair of lines, and the input it came from
+struct Pair {
+    lines: String,
+    origin: &'static str,
+}
+
+fn pairs() -> Vec<Pair> {
+    let common = Params {
+        pairs: COMMON_PAIRS,
+        ..Preset::AocLike.params().unwrap()
+    }
+    .generate();
+    let mut pairs: Vec<Pair> = common
+        .split("\n\n")
+        .map(|lines| Pair {
+            lines: lines.to_owned(),
+            origin: Preset::AocLike.bench_name(),
+        })
+        .collect();
+    for preset in ADVERSARIAL {
+        let input = preset.generate();
+        for _ in 0..ADVERSARIAL_REPEATS {
+            pairs.extend(input.split("\n\n").map(|lines| Pair {
+                lines: lines.to_owned(),
+                origin: preset.bench_name(),
+            }));
+        }
+    }
+    Rng::new(13).shuffle(&mut pairs);
+    pairs
+}
+
+/// Every call's time in ns (sorted), and the index of the slowest pair.
+/// That's the pair with the slowest median over the rounds, as a single slow call may just have been interrupted.
+fn measure(day13: Day13, pairs: &[Pair]) -> (Vec<u64>, usize) {
+    let mut by_pair = vec![Vec::with_capacity(ROUNDS); pairs.len()];
+    for round in 0..=ROUNDS {
+        for (pair, times) in pairs.iter().zip(&mut by_pair) {
+            let start = Instant::now();
+            black_box(day13(black_box(&pair.lines)));
+            let ns = start.elapsed().as_nanos() as u64;
+            if round > 0 {
+                times.push(ns);
+            }
+        }
+    }
+    for times in &mut by_pair {
+        times.sort_unstable();
+    }
+    let slowest_pair = (0..pairs.len())
+        .max_by_key(|&idx| percentile(&by_pair[idx], 0.5))
+        .unwrap();
+    let mut times: Vec<u64> = by_pair.into_iter().flatten().collect();
+    times.sort_unstable();
+    (times, slowest_pair)
+}
+
+fn percentile(sorted: &[u64], fraction: f64) -> u64 {
+    sorted[((sorted.len() - 1) as f64 * fraction).round() as usize]
+}
+
+/// The median cost of timing nothing
+fn timer_overhead() -> u64 {
+    let mut times: Vec<u64> = (0..10_000)
+        .map(|_| {
+            let start = Instant::now();
+            start.elapsed().as_nanos() as u64
+        })
+        .collect();
+    times.sort_unstable();
+    percentile(&times, 0.5)
+}
+
+/// The histogram's buckets are powers of two: bucket `b` holds the times in `2^(b-1)..2^b` ns
+fn bucket(ns: u64) -> usize {
+    (u64::BITS - ns.leading_zeros()) as usize
+}
+
+fn main() {
+    let dir = std::env::var_os("DAY13_LATENCY_DIR")
+        .map_or_else(|| PathBuf::from("target/latency"), PathBuf::from);
+    fs::create_dir_all(&dir).unwrap();
+
+    let pairs = pairs();
+    println!(
+        "{} pairs, {} rounds; timing nothing takes {} ns",
+        pairs.len(),
+        ROUNDS,
+        timer_overhead()
+    );
+    let implementations: Vec<(&str, Day13)> = benched()
+        .map(|(name, day13)| (name, warm(name).unwrap_or(day13)))
+        .collect();
+    let measured: Vec<(Vec<u64>, usize)> = implementations
+        .iter()
+        .map(|&(_, day13)| measure(day13, &pairs))
+        .collect();
+
+    println!();
+    let name_width = implementations
+        .iter()
+        .map(|(name, _)| name.len())
+        .max()
+        .unwrap();
+    print!("{:name_width$}", "implementation");
+    for (percentile, _) in PERCENTILES {
+        print!(" {percentile:>8}");
+    }
+    println!(" {:>8}  slowest pair", "max");
+    for ((name, _), (times, slowest_pair)) in implementations.iter().zip(&measured) {
+        print!("{name:name_width$}");
+        for (_, fraction) in PERCENTILES {
+            print!(" {:>8}", percentile(times, fraction));
+        }
+        println!(
+            " {:>8}  #{slowest_pair} ({})",
+            times.last().unwrap(),
+            pairs[*slowest_pair].origin
+        );
+    }
+    println!("(ns)");
+    println!();
+
+    let header: Vec<_> = implementations
+        .iter()
+        .map(|(name, _)| format!("low\t{name}\thigh"))
+        .collect();
+    let header = format!("Title\t{}", header.join("\t"));
+    // exact values, so low = mid = high
+    let row = |values: Vec<u64>| -> String {
+        let cells: Vec<_> = values
+            .iter()
+            .map(|value| format!("{value}\t{value}\t{value}"))
+            .collect();
+        cells.join("\t")
+    };
+
+    let path = dir.join("latency_percentiles.data");
+    let mut out = fs::File::create(&path).unwrap();
+    writeln!(out, "{header}").unwrap();
+    for (name, fraction) in PERCENTILES {
+        let values = measured
+            .iter()
+            .map(|(times, _)| percentile(times, fraction))
+            .collect();
+        writeln!(out, "{name} (ns)\t{}", row(values)).unwrap();
+    }
+    let values = measured
+        .iter()
+        .map(|(times, _)| *times.last().unwrap())
+        .collect();
+    writeln!(out, "max (ns)\t{}", row(values)).unwrap();
+    println!("wrote {}", path.display());
+
+    let histograms: Vec<Vec<u64>> = measured
+        .iter()
+        .map(|(times, _)| {
+            let mut counts = vec![0; bucket(u64::MAX) + 1];
+            for &ns in times {
+                counts[bucket(ns)] += 1;
+            }
+            counts
+        })
+        .collect();
+    let used = |b: &usize| histograms.iter().any(|counts| counts[*b] > 0);
+    let first = (0..=bucket(u64::MAX)).find(used).unwrap();
+    let last = (0..=bucket(u64::MAX)).rev().find(used).unwrap();
+    let path = dir.join("latency_histogram.data");
+    let mut out = fs::File::create(&path).unwrap();
+    writeln!(out, "{header}").unwrap();
+    for b in first..=last {
+        let values = histograms.iter().map(|counts| counts[b]).collect();
+        writeln!(out, "< {} ns (calls)\t{}", 1u128 << b, row(values)).unwrap();
+    }
+    println!("wrote {}", path.display());
+}
@@ -0,0 +1,90 @@
+//! Sweeps one input-shape parameter at a time, to see how each implementation scales with it.
+//! Each group is named after its parameter and each benchmark after its value, so the results can be plotted
+//! as throughput vs. parameter curves (see `scripts/parse_and_plot_data.sh`).
+
+use std::{fmt::Display, time::Duration};
+
+use criterion::{
+    criterion_group, criterion_main, measurement::WallTime, BenchmarkGroup, BenchmarkId, Criterion,
+    Throughput,
+};
+use day13_compare::generator::{Params, Placement};
+
+#[path = "../tests/support/benched.rs"]
+mod benched;
+use benched::benched;
+
+/// the shape every sweep starts from, varying one parameter
+fn base() -> Params {
+    Params {
+        seed: 13,
+        pairs: 100,
+        depth: 1..=3,
+        width: 0..=4,
+        number_len: 1..=2,
+        shared_prefix_len: 0,
+        first_difference: Placement::Anywhere,
+        equal_percent: 0,
+    }
+}
+
+fn bench_all_impls(group: &mut BenchmarkGroup<WallTime>, value: impl Display, input: &str) {
+    group.throughput(Throughput::Bytes(input.len() as u64));
+    let value = value.to_string();
+    for (impl_name, day13) in benched() {
+        group.bench_with_input(BenchmarkId::new(impl_name, &value), input, |b, i| {
+            b.iter(|| day13(i))
+        });
+    }
+}
+
+fn sweep<T: Display + Copy>(
+    c: &mut Criterion,
+    parameter: &str,
+    values: &[T],
+    params: impl Fn(T) -> Params,
+) {
+    let mut group = c.benchmark_group(format!("Sweep_{parameter}"));
+    for &value in values {
+        bench_all_impls(&mut group, value, &params(value).generate());
+    }
+    group.finish();
+}
+
+fn bench_sweeps(c: &mut Criterion) {
+    sweep(c, "depth", &[1, 2, 4, 8, 16, 32, 64, 128], |depth| Params {
+        depth: depth..=depth,
+        ..base()
+    });
+    sweep(c, "number_len", &[1, 2, 4, 8, 16, 32, 64, 128], |len| {
+        Params {
+            number_len: len..=len,
+            ..base()
+        }
+    });
+    // the lines differ right after the prefix, so this is (almost) all of each line
+    sweep(
+        c,
+        "shared_prefix_len",
+        &[1, 4, 16, 64, 256, 1024, 4096],
+        |len| Params {
+            shared_prefix_len: len,
+            first_difference: Placement::First,
+            ..base()
+        },
+    );
+    sweep(c, "pairs", &[1, 10, 100, 1_000, 10_000], |pairs| Params {
+        pairs,
+        ..base()
+    });
+}
+
+criterion_group! {
+    name = benches;
+    // 13 implementations * ~8 values per sweep; the default times would take most of an hour
+    config = Criterion::default()
+        .warm_up_time(Duration::from_secs(1))
+        .measurement_time(Duration::from_secs(2));
+    targets = bench_sweeps
+}
+criterion_main!(benches);
@@ -0,0 +1,15 @@
+#!/bin/sh
+# Builds + tests the library without the `std` feature
+# this should be run from the root dir of the project
+set -e
+
+# the library is always `#![no_std]`, so this fails to compile if anything outside the `std`
+# feature reaches for `std::`
+cargo test --lib --no-default-features
+
+# a target without `std` at all is the real proof, but needs `rustup target add thumbv7em-none-eabihf`
+if rustup target list --installed 2>/dev/null | grep -q "thumbv7em-none-eabihf"; then
+  cargo build --lib --no-default-features --target thumbv7em-none-eabihf
+else
+  echo >&2 "skipping bare-metal build: target 'thumbv7em-none-eabihf' is not installed"
+fi
@@ -0,0 +1,19 @@
+#!/bin/sh
+# Runs the library's tests under Miri, which checks them (and the `unsafe` in the dependencies they reach,
+# e.g. logos' lexers) for undefined behavior. The library itself is `#![forbid(unsafe_code)]`.
+# Needs `rustup +nightly component add miri`.
+# this should be run from the root dir of the project
+# extra args are passed on to the test binary, e.g. `./scripts/miri.sh naive_slice` to only run a subset
+set -e
+
+if ! cargo +nightly miri --version >/dev/null 2>&1; then
+  echo >&2 "miri is not installed, run 'rustup +nightly component add miri'"
+  exit 1
+fi
+
+# the interpreter is orders of magnitude slower than native code, so this skips
+# the 8 threads x 200 iterations of the shared pools and the 300k levels deep lines
+cargo +nightly miri test --lib -- \
+  --skip pools_shared_between_threads \
+  --skip deep_nesting_does_not_overflow_the_stack \
+  "$@"
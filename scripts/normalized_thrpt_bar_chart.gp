@@ -1,6 +1,6 @@
 #!/usr/bin/gnuplot -c
 
-# expects data in the format produced by ./cri_to_gp_data.py
+# expects data in the format produced by `bench_report tsv` (tools/bench_report)
 
 if(ARGC!=2){
   exit error "Received ".ARGC." args, expected 2 args: {data file} {1-based column to normalize by)}"
@@ -14,6 +14,10 @@ set style data histogram
 set style histogram cluster gap 2
 set terminal png noenhanced size 2048,1536
 
+# `bench_report` appends the environment as `# key = value` lines, which gnuplot skips as comments
+environment=system("sed -n 's/^# summary = //p' '".filename."'")
+set title environment noenhanced
+
 set xlabel "Inputs"
 set ylabel "Normalized Throughput"
 
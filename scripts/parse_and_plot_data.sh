@@ -9,26 +9,74 @@ if [ ! -d "./assets" ]; then
   exit 1
 fi
 
-if [ ! -f "./assets/full_raw.txt" ]; then
-  echo >&2 "could not find file './assets/full_raw.txt"
+# turns criterion's results (under ./target/criterion) into tables, see tools/bench_report
+report() {
+  cargo run --quiet --release --package bench_report -- "$@"
+}
+
+if ! report tsv > ./assets/full_data.data; then
   echo >&2 "Make sure you ran the './scripts/run_all_benches.sh' command prior to this script!"
   exit 1
 fi
-
-./scripts/cri_to_gp_data.py < ./assets/full_raw.txt > ./assets/full_data.data
 ./scripts/thrpt_bar_chart.gp ./assets/full_data.data > ./assets/full_data.png
 
 # extract & plot data excluding the 10kb number inputs
 grep -v "number" ./assets/full_data.data > ./assets/data_excluding_single_number.data
 ./scripts/thrpt_bar_chart.gp ./assets/data_excluding_single_number.data > ./assets/data_excluding_single_number.png
 
-# extract data for the 10kb number inputs
+# extract data for the 10kb number inputs, keeping the environment comments for the plot's title
 head -n1 ./assets/full_data.data > ./assets/data_only_single_number.data
-grep "number" ./assets/full_data.data >> ./assets/data_only_single_number.data
+grep -e "number" -e "^#" ./assets/full_data.data >> ./assets/data_only_single_number.data
 ./scripts/thrpt_bar_chart.gp ./assets/data_only_single_number.data > ./assets/data_only_single_number.png
 
 # extract & plot data for the 'naive' impls
-grep --after-context=2 --no-group-separator "naive" ./assets/full_raw.txt | ./scripts/cri_to_gp_data.py > ./assets/data_only_naive.data
+report tsv --filter naive > ./assets/data_only_naive.data
 ./scripts/thrpt_bar_chart.gp ./assets/data_only_naive.data > ./assets/data_only_naive.png
 ## plot normalized data for naive
 ./scripts/normalized_thrpt_bar_chart.gp ./assets/data_only_naive.data 2 > ./assets/normalized_only_naive.png
+## and the README's tables for them
+report markdown --filter naive --normalize "naive::no_pool" > ./assets/naive_tables.md
+
+# plot each parameter sweep as throughput vs. that parameter
+for parameter in depth number_len shared_prefix_len pairs; do
+  if ! report tsv --group "Sweep_$parameter" --sweep > "./assets/sweep_$parameter.data"; then
+    echo >&2 "skipping the $parameter sweep"
+    rm "./assets/sweep_$parameter.data"
+    continue
+  fi
+  ./scripts/thrpt_line_chart.gp "./assets/sweep_$parameter.data" "$parameter" log > "./assets/sweep_$parameter.png"
+done
+
+# plot how far each implementation's samples spread when the input's placement changes between them
+if report tsv --group Align_A --spread > ./assets/alignment_spread.data; then
+  ./scripts/thrpt_bar_chart.gp ./assets/alignment_spread.data > ./assets/alignment_spread.png
+else
+  echo >&2 "skipping the alignment plot"
+  rm ./assets/alignment_spread.data
+fi
+
+# plot each implementation's throughput with cold caches, next to the same loop on one (hot) input
+if report tsv --group Cold_A > ./assets/cold_cache.data; then
+  ./scripts/thrpt_bar_chart.gp ./assets/cold_cache.data > ./assets/cold_cache.png
+else
+  echo >&2 "skipping the cold cache plot"
+  rm ./assets/cold_cache.data
+fi
+
+# plot what each implementation allocates on each input
+for metric in alloc_count alloc_bytes alloc_peak_live; do
+  if [ ! -f "./assets/$metric.data" ]; then
+    echo >&2 "skipping the $metric plot: could not find './assets/$metric.data'"
+    continue
+  fi
+  ./scripts/thrpt_bar_chart.gp "./assets/$metric.data" "$metric" > "./assets/$metric.png"
+done
+
+# plot each implementation's per-pair latency, as a histogram and as percentiles
+for table in latency_histogram latency_percentiles; do
+  if [ ! -f "./assets/$table.data" ]; then
+    echo >&2 "skipping the $table plot: could not find './assets/$table.data'"
+    continue
+  fi
+  ./scripts/thrpt_bar_chart.gp "./assets/$table.data" "$table" > "./assets/$table.png"
+done
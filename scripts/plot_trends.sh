@@ -0,0 +1,12 @@
+#!/bin/sh
+# Plots the throughput of each implementation on each input across the runs recorded in the history
+# (see `bench_report record`, which ./scripts/run_all_benches.sh calls)
+# assumes you are calling this from the root directory
+# args: [history file, './bench_history.jsonl' by default]
+# output will go to './target/trend/', one plot per input
+
+cargo run --quiet --release --package bench_report -- trend --history "${1:-./bench_history.jsonl}" --out ./target/trend |
+  while IFS="$(printf '\t')" read -r data input; do
+    echo >&2 "plotting $input"
+    ./scripts/thrpt_line_chart.gp "$data" "run" labels > "${data%.data}.png"
+  done
@@ -1,3 +1,13 @@
 #!/bin/sh
 # this should be run from the root dir of the project 
-cargo bench --bench day13_impls -- --format terse --quiet "Day13_A/" > ./assets/full_raw.txt
+# the results are saved under ./target/criterion, see ./scripts/parse_and_plot_data.sh
+# along with the machine and build environment they're measured in, which the tables and plots carry
+cargo run --quiet --release --package bench_report -- env --save
+cargo bench --bench day13_impls -- --quiet "Day13_A/"
+# appends this run to ./bench_history.jsonl, see ./scripts/plot_trends.sh
+cargo run --quiet --release --package bench_report -- record
+cargo bench --bench sweeps -- --quiet "Sweep_"
+cargo bench --bench alignment -- --quiet "Align_A/"
+cargo bench --bench cold_cache -- --quiet "Cold_A/"
+DAY13_ALLOC_DIR=./assets cargo bench --bench allocations
+DAY13_LATENCY_DIR=./assets cargo bench --bench latency
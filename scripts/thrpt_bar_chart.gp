@@ -1,6 +1,7 @@
 #!/usr/bin/gnuplot -c
 
-# expects data in the format produced by ./cri_to_gp_data.py
+# expects data in the format produced by `bench_report tsv` (tools/bench_report, or `cargo bench --bench allocations` / `--bench latency`)
+# args: {data file} [y axis label, "Throughput" by default]
 filename=ARG1
 
 set datafile separator tab
@@ -8,8 +9,12 @@ set style data histogram
 set style histogram cluster errorbars gap 2 lw 1
 set terminal png noenhanced size 2048,1536
 
+# `bench_report` appends the environment as `# key = value` lines, which gnuplot skips as comments
+environment=system("sed -n 's/^# summary = //p' '".filename."'")
+set title environment noenhanced
+
 set xlabel "Inputs"
-set ylabel "Throughput"
+set ylabel (ARGC >= 2 ? ARG2 : "Throughput")
 
 set grid ytics
 
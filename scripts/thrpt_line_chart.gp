@@ -0,0 +1,48 @@
+#!/usr/bin/gnuplot -c
+
+# expects data in the format produced by `bench_report tsv --sweep` or `bench_report trend` (tools/bench_report)
+# args: {data file} {x axis label} [log, for a logarithmic x axis | labels, if the first column is text, like trend's runs]
+
+if(ARGC<2){
+  exit error "Received ".ARGC." args, expected 2-3 args: {data file} {x axis label} [log]"
+}
+
+filename=ARG1
+
+set datafile separator tab
+set terminal png noenhanced size 2048,1536
+
+# `bench_report` appends the environment as `# key = value` lines, which gnuplot skips as comments
+environment=system("sed -n 's/^# summary = //p' '".filename."'")
+set title environment noenhanced
+
+# the first header field is the throughput unit
+unit=system("head -n1 '".filename."' | cut -f1")
+set xlabel ARG2
+set ylabel "Throughput (".unit.")"
+labels=(ARGC>=3 && ARG3 eq "labels")
+if (ARGC>=3 && ARG3 eq "log") {
+  set logscale x 2
+}
+if (labels) {
+  set xtics rotate by -45
+}
+
+set grid xtics ytics
+set key outside right top
+set yrange [0:*]
+
+stats filename u 0 nooutput
+max_col = STATS_columns
+
+# column(i)   : low
+# ''    (i+1) : mid
+# ''    (i+2) : high
+# syntax:
+# using {x}:{y}:{y.low}:{y.high} with yerrorlines {title col(i+1) --this is the title for the column,taken from the header--}
+# with labels, the rows are evenly spaced, and each is labeled with its first column
+if (labels) {
+  plot for [i=2:max_col:3] filename using 0:(column(i+1)):i:(column(i+2)):xtic(1) with yerrorlines lw 2 title col(i+1)
+} else {
+  plot for [i=2:max_col:3] filename using 1:(column(i+1)):i:(column(i+2)) with yerrorlines lw 2 title col(i+1)
+}
@@ -0,0 +1,179 @@
+//! Runs one implementation of part A on a file (or stdin) and prints the answer.
+//!
+//! usage: day13 [--impl <name>] [--stats] [<file>]
+//!        day13 --list
+//!
+//! Exit codes follow sysexits.h: 64 for bad arguments, 65 for invalid input, 74 for I/O errors.
+
+use day13_compare::{
+    limits::Limits,
+    naive, naive_slice,
+    registry::{self, Implementation},
+    res_pool::PoolStats,
+};
+use std::{
+    any::Any,
+    io::{self, Read},
+    panic,
+    process::ExitCode,
+};
+
+const USAGE: &str = "usage: day13 [--impl <name>] [--stats] [<file>]
+       day13 --list
+
+Prints the part A answer for <file>, or stdin if there's no <file> or it is `-`.
+--impl   the implementation to use (default: naive_slice::pooled), see --list
+--stats  also print the pool usage to stderr, for naive::{no_pool,pooled} and naive_slice::{no_pool,pooled}";
+
+const EXIT_USAGE: u8 = 64;
+const EXIT_INVALID_INPUT: u8 = 65;
+const EXIT_IO: u8 = 74;
+
+struct Args {
+    implementation: &'static Implementation,
+    stats: Option<Stats>,
+    path: Option<String>,
+}
+
+/// The pool usage of running an implementation, see `res_pool::Instrumented`
+type Stats = fn(&str) -> Vec<(&'static str, PoolStats)>;
+
+fn stats(name: &str) -> Option<Stats> {
+    Some(match name {
+        "naive::no_pool" => |input| {
+            let (_, lists, strings) = naive::no_pool::day13_with_stats(input);
+            vec![("lists", lists), ("strings", strings)]
+        },
+        "naive::pooled" => |input| {
+            let (_, lists, strings) = naive::pooled::day13_with_stats(input);
+            vec![("lists", lists), ("strings", strings)]
+        },
+        "naive_slice::no_pool" => {
+            |input| vec![("lists", naive_slice::no_pool::day13_with_stats(input).1)]
+        }
+        "naive_slice::pooled" => {
+            |input| vec![("lists", naive_slice::pooled::day13_with_stats(input).1)]
+        }
+        _ => return None,
+    })
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Option<Args>, String> {
+    let mut implementation = registry::DEFAULT.to_owned();
+    let mut with_stats = false;
+    let mut path = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return Ok(None);
+            }
+            "--list" => {
+                for implementation in registry::IMPLEMENTATIONS {
+                    let validates = if implementation.validates {
+                        " (validates input)"
+                    } else {
+                        ""
+                    };
+                    println!("{}{validates}", implementation.name);
+                }
+                return Ok(None);
+            }
+            "--impl" => {
+                implementation = args.next().ok_or("--impl needs a name")?;
+            }
+            "--stats" => with_stats = true,
+            "-" => path = None,
+            _ if arg.starts_with('-') => return Err(format!("unknown option `{arg}`")),
+            _ if path.is_some() => return Err("expected at most one file".to_owned()),
+            _ => path = Some(arg),
+        }
+    }
+    let implementation = registry::find(&implementation).ok_or_else(|| {
+        format!("unknown implementation `{implementation}`, see --list for the options")
+    })?;
+    let stats = match stats(implementation.name) {
+        None if with_stats => {
+            return Err(format!(
+                "`{}` doesn't record pool stats",
+                implementation.name
+            ))
+        }
+        stats => stats.filter(|_| with_stats),
+    };
+    Ok(Some(Args {
+        implementation,
+        stats,
+        path,
+    }))
+}
+
+fn read_input(path: Option<&str>) -> io::Result<String> {
+    match path {
+        Some(path) => std::fs::read_to_string(path),
+        None => {
+            let mut input = String::new();
+            io::stdin().read_to_string(&mut input)?;
+            Ok(input)
+        }
+    }
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message
+    } else {
+        "(no message)"
+    }
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(Some(args)) => args,
+        Ok(None) => return ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("error: {message}\n\n{USAGE}");
+            return ExitCode::from(EXIT_USAGE);
+        }
+    };
+
+    let input = match read_input(args.path.as_deref()) {
+        Ok(input) => input,
+        Err(err) if err.kind() == io::ErrorKind::InvalidData => {
+            eprintln!("error: the input is not UTF-8");
+            return ExitCode::from(EXIT_INVALID_INPUT);
+        }
+        Err(err) => {
+            let source = args.path.as_deref().unwrap_or("stdin");
+            eprintln!("error: couldn't read {source}: {err}");
+            return ExitCode::from(EXIT_IO);
+        }
+    };
+
+    // the implementations that don't validate their input panic on (some) invalid input,
+    // which is reported like any other invalid input
+    panic::set_hook(Box::new(|_| {}));
+    let try_day13 = args.implementation.try_day13;
+    match panic::catch_unwind(|| try_day13(&input, &Limits::UNBOUNDED)) {
+        Ok(Ok(answer)) => {
+            println!("{answer}");
+            // the input is valid, so this doesn't panic
+            if let Some(stats) = args.stats {
+                for (pool, stats) in stats(&input) {
+                    eprintln!("{} {pool}: {stats}", args.implementation.name);
+                }
+            }
+            ExitCode::SUCCESS
+        }
+        Ok(Err(err)) => {
+            eprintln!("error: invalid input: {err}");
+            ExitCode::from(EXIT_INVALID_INPUT)
+        }
+        Err(payload) => {
+            eprintln!("error: invalid input: {}", panic_message(&*payload));
+            ExitCode::from(EXIT_INVALID_INPUT)
+        }
+    }
+}
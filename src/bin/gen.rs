@@ -0,0 +1,157 @@
+//! Writes a generated input to stdout, see `day13_compare::generator`.
+//!
+//! usage: gen [--preset <name>] [<option>...]
+//!        gen --list
+//!
+//! Exit codes follow sysexits.h: 64 for bad arguments, 74 for I/O errors.
+
+use day13_compare::generator::{Params, Placement, Preset};
+use std::{
+    io::{self, Write},
+    ops::RangeInclusive,
+    process::ExitCode,
+    str::FromStr,
+};
+
+const USAGE: &str = "usage: gen [--preset <name>] [<option>...]
+       gen --list
+
+Writes a generated input to stdout. The options override the preset's params (default: aoc-like),
+ranges are either <n> or <min>-<max>.
+--preset            a named input, see --list
+--seed <n>
+--pairs <n>
+--depth <range>     nesting depth of each line, the outermost list being 1
+--width <range>     elements per list
+--number-len <range>
+--shared-prefix <n> bytes the lines of an unequal pair agree on
+--first-difference first|last|anywhere
+--equal-percent <n> share of pairs whose lines are identical";
+
+const EXIT_USAGE: u8 = 64;
+const EXIT_IO: u8 = 74;
+
+fn parse<T: FromStr>(flag: &str, value: Option<String>) -> Result<T, String> {
+    let value = value.ok_or_else(|| format!("{flag} needs a value"))?;
+    value
+        .parse()
+        .map_err(|_| format!("invalid value `{value}` for {flag}"))
+}
+
+fn parse_range(flag: &str, value: Option<String>) -> Result<RangeInclusive<usize>, String> {
+    let value = value.ok_or_else(|| format!("{flag} needs a value"))?;
+    let (min, max) = value.split_once('-').unwrap_or((&value, &value));
+    match (min.parse(), max.parse()) {
+        (Ok(min), Ok(max)) => Ok(min..=max),
+        _ => Err(format!("invalid range `{value}` for {flag}")),
+    }
+}
+
+/// an option's change to the preset's params, applied once the preset is known
+type Override = Box<dyn FnOnce(&mut Params)>;
+
+/// `None` if there's nothing to generate
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Option<String>, String> {
+    let mut preset = Preset::AocLike;
+    let mut overrides: Vec<Override> = Vec::new();
+    while let Some(arg) = args.next() {
+        let flag = arg.as_str();
+        match flag {
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return Ok(None);
+            }
+            "--list" => {
+                for preset in Preset::ALL {
+                    let fixed = if preset.params().is_none() {
+                        " (fixed)"
+                    } else {
+                        ""
+                    };
+                    println!("{}{fixed}", preset.name());
+                }
+                return Ok(None);
+            }
+            "--preset" => {
+                let name = args.next().ok_or("--preset needs a name")?;
+                preset = Preset::find(&name).ok_or_else(|| {
+                    format!("unknown preset `{name}`, see --list for the options")
+                })?;
+            }
+            "--seed" => {
+                let seed = parse(flag, args.next())?;
+                overrides.push(Box::new(move |params| params.seed = seed));
+            }
+            "--pairs" => {
+                let pairs = parse(flag, args.next())?;
+                overrides.push(Box::new(move |params| params.pairs = pairs));
+            }
+            "--depth" => {
+                let depth = parse_range(flag, args.next())?;
+                overrides.push(Box::new(move |params| params.depth = depth));
+            }
+            "--width" => {
+                let width = parse_range(flag, args.next())?;
+                overrides.push(Box::new(move |params| params.width = width));
+            }
+            "--number-len" => {
+                let number_len = parse_range(flag, args.next())?;
+                overrides.push(Box::new(move |params| params.number_len = number_len));
+            }
+            "--shared-prefix" => {
+                let len = parse(flag, args.next())?;
+                overrides.push(Box::new(move |params| params.shared_prefix_len = len));
+            }
+            "--first-difference" => {
+                let placement = match args.next().as_deref() {
+                    Some("first") => Placement::First,
+                    Some("last") => Placement::Last,
+                    Some("anywhere") => Placement::Anywhere,
+                    _ => return Err(format!("{flag} needs one of first, last, anywhere")),
+                };
+                overrides.push(Box::new(move |params| params.first_difference = placement));
+            }
+            "--equal-percent" => {
+                let percent = parse(flag, args.next())?;
+                overrides.push(Box::new(move |params| params.equal_percent = percent));
+            }
+            _ => return Err(format!("unexpected argument `{arg}`")),
+        }
+    }
+    let Some(mut params) = preset.params() else {
+        if !overrides.is_empty() {
+            return Err(format!(
+                "the `{}` preset is fixed, it takes no options",
+                preset.name()
+            ));
+        }
+        return Ok(Some(preset.generate()));
+    };
+    for apply in overrides {
+        apply(&mut params);
+    }
+    params.validate()?;
+    Ok(Some(params.generate()))
+}
+
+fn main() -> ExitCode {
+    let input = match parse_args(std::env::args().skip(1)) {
+        Ok(Some(input)) => input,
+        Ok(None) => return ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("error: {message}\n\n{USAGE}");
+            return ExitCode::from(EXIT_USAGE);
+        }
+    };
+    let mut stdout = io::stdout().lock();
+    match stdout
+        .write_all(input.as_bytes())
+        .and_then(|_| stdout.flush())
+    {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: couldn't write the input: {err}");
+            ExitCode::from(EXIT_IO)
+        }
+    }
+}
@@ -0,0 +1,364 @@
+//! Seeded inputs, from AoC-like files to adversarial ones (deep nesting, wide lists, long numbers, long shared prefixes).
+//! The same [Params] (seed included) always generate the same input, and every generated input is valid.
+
+use alloc::{format, string::String, vec::Vec};
+use core::ops::{Range, RangeInclusive};
+
+/// SplitMix64; small, fast and seedable, which is all the generator needs.
+#[derive(Clone, Debug)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// uniform in `0..n`, `n` can't be 0
+    pub fn below(&mut self, n: usize) -> usize {
+        ((self.next_u64() as u128 * n as u128) >> 64) as usize
+    }
+
+    /// uniform in `range`, which can't be empty
+    pub fn in_range(&mut self, range: &RangeInclusive<usize>) -> usize {
+        range.start() + self.below(range.end() - range.start() + 1)
+    }
+
+    /// true `percent`% of the time
+    pub fn percent(&mut self, percent: u8) -> bool {
+        self.below(100) < percent as usize
+    }
+
+    /// Fisher-Yates
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+}
+
+/// Which of the candidate numbers (those past the shared prefix) the lines of an unequal pair first differ at
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Placement {
+    First,
+    Last,
+    Anywhere,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Params {
+    pub seed: u64,
+    pub pairs: usize,
+    /// nesting depth of each line, the outermost list being depth 1.
+    /// One path through the line reaches the chosen depth; the lists off that path rarely nest,
+    /// so a line's length grows with its depth linearly, not exponentially.
+    pub depth: RangeInclusive<usize>,
+    /// number of elements in each list
+    pub width: RangeInclusive<usize>,
+    /// number of digits in each number, which never have a leading zero
+    pub number_len: RangeInclusive<usize>,
+    /// the lines of an unequal pair agree on at least this many bytes.
+    /// The outermost list of the left line is padded with numbers until one starts past the prefix.
+    pub shared_prefix_len: usize,
+    /// where the first difference falls, past the shared prefix.
+    /// The right line replaces one number of the left line with a different one, which decides the pair.
+    pub first_difference: Placement,
+    /// the percentage of pairs whose lines are identical
+    pub equal_percent: u8,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Preset::AocLike.params().unwrap()
+    }
+}
+
+impl Params {
+    pub fn validate(&self) -> Result<(), &'static str> {
+        if self.depth.is_empty() || *self.depth.start() == 0 {
+            Err("depth must be a non-empty range of 1 or more")
+        } else if self.width.is_empty() {
+            Err("width must be a non-empty range")
+        } else if self.number_len.is_empty() || *self.number_len.start() == 0 {
+            Err("number_len must be a non-empty range of 1 or more")
+        } else if self.equal_percent > 100 {
+            Err("equal_percent must be at most 100")
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Pairs are separated by a blank line, and there's no trailing newline.
+    ///
+    /// # Panics
+    /// if the params are invalid, see [Params::validate]
+    pub fn generate(&self) -> String {
+        if let Err(reason) = self.validate() {
+            panic!("invalid generator params: {reason}");
+        }
+        let mut rng = Rng::new(self.seed);
+        let mut out = String::new();
+        let mut numbers = Vec::new();
+        for idx in 0..self.pairs {
+            if idx > 0 {
+                out.push_str("\n\n");
+            }
+            let equal = rng.percent(self.equal_percent);
+            let left = self.line(&mut rng, &mut out, &mut numbers, !equal);
+            out.push('\n');
+            if equal {
+                out.extend_from_within(left);
+                continue;
+            }
+            let prefix_end = left.start + self.shared_prefix_len;
+            let candidates = &numbers[numbers.partition_point(|n| n.start < prefix_end)..];
+            let changed = match self.first_difference {
+                Placement::First => candidates[0].clone(),
+                Placement::Last => candidates[candidates.len() - 1].clone(),
+                Placement::Anywhere => candidates[rng.below(candidates.len())].clone(),
+            };
+            let replacement = self.different_number(&mut rng, &out[changed.clone()]);
+            out.extend_from_within(left.start..changed.start);
+            out.push_str(&replacement);
+            out.extend_from_within(changed.end..left.end);
+        }
+        out
+    }
+
+    /// Appends a line to `out` and returns its span; `numbers` gets the spans of its numbers, in order.
+    /// Iterative, so that deep lines can't overflow the stack.
+    fn line(
+        &self,
+        rng: &mut Rng,
+        out: &mut String,
+        numbers: &mut Vec<Range<usize>>,
+        needs_candidate: bool,
+    ) -> Range<usize> {
+        struct Open {
+            level: usize,
+            remaining: usize,
+            /// the element (counting down) that continues the path to `depth`
+            deeper: Option<usize>,
+            empty: bool,
+        }
+        let start = out.len();
+        numbers.clear();
+        let depth = rng.in_range(&self.depth);
+        let open = |rng: &mut Rng, level: usize, on_path: bool| {
+            let mut remaining = rng.in_range(&self.width);
+            let mut deeper = None;
+            if on_path && level < depth {
+                remaining = remaining.max(1);
+                deeper = Some(rng.below(remaining));
+            }
+            Open {
+                level,
+                remaining,
+                deeper,
+                empty: true,
+            }
+        };
+
+        // lists off the path to `depth` nest with a chance of 1 / (1 + max width)
+        let nest_odds = self.width.end() + 1;
+        let mut stack = Vec::with_capacity(depth);
+        out.push('[');
+        stack.push(open(rng, 1, true));
+        while let Some(top) = stack.last_mut() {
+            let mut padding = false;
+            if top.remaining == 0 {
+                let outermost = top.level == 1;
+                let has_candidate = numbers
+                    .last()
+                    .is_some_and(|n| n.start >= start + self.shared_prefix_len);
+                if !(outermost && needs_candidate && !has_candidate) {
+                    out.push(']');
+                    stack.pop();
+                    continue;
+                }
+                // pad the outermost list
+                top.remaining = 1;
+                top.deeper = None;
+                padding = true;
+            }
+            if !core::mem::take(&mut top.empty) {
+                out.push(',');
+            }
+            top.remaining -= 1;
+            let level = top.level;
+            let deeper = top.deeper == Some(0);
+            top.deeper = top.deeper.and_then(|d| d.checked_sub(1));
+            if deeper {
+                out.push('[');
+                stack.push(open(rng, level + 1, true));
+            } else if !padding && level < depth && rng.below(nest_odds) == 0 {
+                out.push('[');
+                stack.push(open(rng, level + 1, false));
+            } else {
+                let number_start = out.len();
+                self.number(rng, out);
+                numbers.push(number_start..out.len());
+            }
+        }
+        start..out.len()
+    }
+
+    fn number(&self, rng: &mut Rng, out: &mut String) {
+        let len = rng.in_range(&self.number_len);
+        let first = if len == 1 { 0 } else { 1 };
+        out.push(char::from(
+            b'0' + first + rng.below(10 - first as usize) as u8,
+        ));
+        for _ in 1..len {
+            out.push(char::from(b'0' + rng.below(10) as u8));
+        }
+    }
+
+    fn different_number(&self, rng: &mut Rng, old: &str) -> String {
+        let mut new = String::new();
+        self.number(rng, &mut new);
+        if new == old {
+            let last = new.pop().unwrap();
+            new.push(if last == '9' {
+                '0'
+            } else {
+                char::from(last as u8 + 1)
+            });
+        }
+        new
+    }
+}
+
+/// Named inputs; the [Preset::BENCH] ones are exactly the inputs `benches/day13_impls.rs` has always measured.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Preset {
+    Sample,
+    SampleRepeated1K,
+    LongNumberLastDigit,
+    LongNumberFirstDigit,
+    AocLike,
+    DeepNesting,
+    LongSharedPrefix,
+}
+
+impl Preset {
+    pub const ALL: [Preset; 7] = [
+        Preset::Sample,
+        Preset::SampleRepeated1K,
+        Preset::LongNumberLastDigit,
+        Preset::LongNumberFirstDigit,
+        Preset::AocLike,
+        Preset::DeepNesting,
+        Preset::LongSharedPrefix,
+    ];
+
+    pub const BENCH: [Preset; 4] = [
+        Preset::Sample,
+        Preset::SampleRepeated1K,
+        Preset::LongNumberLastDigit,
+        Preset::LongNumberFirstDigit,
+    ];
+
+    /// the name used by the `gen` binary
+    pub fn name(self) -> &'static str {
+        match self {
+            Preset::Sample => "sample",
+            Preset::SampleRepeated1K => "sample-1k",
+            Preset::LongNumberLastDigit => "long-number-last-digit",
+            Preset::LongNumberFirstDigit => "long-number-first-digit",
+            Preset::AocLike => "aoc-like",
+            Preset::DeepNesting => "deep-nesting",
+            Preset::LongSharedPrefix => "long-shared-prefix",
+        }
+    }
+
+    /// the name used in benchmark ids, which predates the presets
+    pub fn bench_name(self) -> &'static str {
+        match self {
+            Preset::Sample => "original sample",
+            Preset::SampleRepeated1K => "orig sample repeated 1K",
+            Preset::LongNumberLastDigit => "single 10kB number, last digit different",
+            Preset::LongNumberFirstDigit => "single 10kB number, first digit different",
+            other => other.name(),
+        }
+    }
+
+    pub fn find(name: &str) -> Option<Preset> {
+        Preset::ALL.into_iter().find(|preset| preset.name() == name)
+    }
+
+    /// `None` for the fixed presets, which don't come from [Params]
+    pub fn params(self) -> Option<Params> {
+        let params = match self {
+            Preset::Sample
+            | Preset::SampleRepeated1K
+            | Preset::LongNumberLastDigit
+            | Preset::LongNumberFirstDigit => return None,
+            Preset::AocLike => Params {
+                seed: 13,
+                pairs: 150,
+                depth: 1..=5,
+                width: 0..=5,
+                number_len: 1..=2,
+                shared_prefix_len: 0,
+                first_difference: Placement::Anywhere,
+                equal_percent: 0,
+            },
+            Preset::DeepNesting => Params {
+                seed: 13,
+                pairs: 10,
+                depth: 1000..=1000,
+                width: 0..=2,
+                number_len: 1..=3,
+                shared_prefix_len: 0,
+                first_difference: Placement::Last,
+                equal_percent: 0,
+            },
+            Preset::LongSharedPrefix => Params {
+                seed: 13,
+                pairs: 10,
+                depth: 1..=3,
+                width: 1..=4,
+                number_len: 1..=9,
+                shared_prefix_len: 10_000,
+                first_difference: Placement::First,
+                equal_percent: 10,
+            },
+        };
+        Some(params)
+    }
+
+    pub fn generate(self) -> String {
+        match self {
+            Preset::Sample => crate::SAMPLE.into(),
+            Preset::SampleRepeated1K => {
+                let mut input = format!("{}\n\n", crate::SAMPLE).repeat(1000);
+                input.truncate(input.len() - 2);
+                input
+            }
+            Preset::LongNumberLastDigit | Preset::LongNumberFirstDigit => {
+                // 9997 digits, one of which differs between the lines
+                let pattern = "1029637485".repeat(1000);
+                let digits = &pattern[1..pattern.len() - 2];
+                let (left, right): (String, String) = if self == Preset::LongNumberLastDigit {
+                    let (most, last) = digits.split_at(digits.len() - 1);
+                    (
+                        digits.into(),
+                        format!("{most}{}", if last == "1" { 0 } else { 1 }),
+                    )
+                } else {
+                    (format!("5{}", &digits[1..]), digits.into())
+                };
+                format!("[{left}]\n[{right}]")
+            }
+            _ => self.params().unwrap().generate(),
+        }
+    }
+}
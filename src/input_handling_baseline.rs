@@ -1,11 +1,28 @@
 //! shared input parsing framework w/ trivial compare function
 
-use crate::shared::day13_framework;
-use std::cmp::Ordering;
+use crate::shared::{day13_framework, day13_framework_within, limits::Bounds, Error, Pair};
+use core::cmp::Ordering;
 
 pub fn day13(input: &str) -> usize {
     day13_framework(input, compare)
 }
+
+/// [day13], but returns an error on input beyond `limits`.
+/// Bounding the depth or number length makes this read every byte, which the trivial comparison
+/// otherwise doesn't.
+/// The lines are not validated: invalid packets still panic, or give a meaningless answer.
+pub fn try_day13(input: &str, limits: &impl Bounds) -> Result<usize, Error> {
+    try_day13_observed(input, limits, |_| {})
+}
+
+/// [try_day13], also reporting each pair to `on_pair`
+pub fn try_day13_observed(
+    input: &str,
+    limits: &impl Bounds,
+    on_pair: impl FnMut(Pair),
+) -> Result<usize, Error> {
+    day13_framework_within(input, limits, compare, on_pair)
+}
 fn compare(left: &str, right: &str) -> Ordering {
     left.len()
         .cmp(&right.len())
@@ -0,0 +1,99 @@
+//! Per-pair results as JSON lines (one object per line), written by hand so that the library
+//! doesn't need a serialization dependency. For the sample:
+//! ```text
+//! {"type":"pair","index":1,"ordering":"less","left":{"start":0,"end":11},"right":{"start":12,"end":23},"error":null}
+//! ...
+//! {"type":"summary","implementation":"naive_slice::pooled","pairs":8,"answer":13,"error":null}
+//! ```
+//! A pair that was rejected has `"ordering":null` and an `"error":{"kind":..,"message":..}`.
+//! Like `try_day13`, the run stops at the first error, and the summary's `answer` is then `null`.
+
+use crate::{limits::Limits, registry::Implementation, Error, Pair};
+use core::cmp::Ordering;
+use std::{
+    io::{self, Write},
+    string::ToString,
+};
+
+/// Runs `implementation` over `input`, and writes an object for each pair to `out`,
+/// followed by a summary. Returns what `try_day13` returns, unless writing fails.
+///
+/// An implementation that doesn't [validate](Implementation::validates) its input may panic
+/// on invalid lines, as it would in `try_day13`.
+pub fn write(
+    implementation: &Implementation,
+    input: &str,
+    limits: &Limits,
+    out: &mut impl Write,
+) -> io::Result<Result<usize, Error>> {
+    let mut written = Ok(());
+    let mut pairs = 0;
+    let answer = (implementation.try_day13_observed)(input, limits, &mut |pair| {
+        pairs += 1;
+        if written.is_ok() {
+            written = write_pair(out, &pair);
+        }
+    });
+    written?;
+
+    write!(out, r#"{{"type":"summary","implementation":"#)?;
+    write_string(out, implementation.name)?;
+    write!(out, r#","pairs":{pairs},"answer":"#)?;
+    match answer {
+        Ok(answer) => write!(out, "{answer}")?,
+        Err(_) => write!(out, "null")?,
+    }
+    write!(out, r#","error":"#)?;
+    write_error(out, answer.err())?;
+    writeln!(out, "}}")?;
+    Ok(answer)
+}
+
+fn write_pair(out: &mut impl Write, pair: &Pair) -> io::Result<()> {
+    let ordering = match pair.result {
+        Ok(Ordering::Less) => r#""less""#,
+        Ok(Ordering::Equal) => r#""equal""#,
+        Ok(Ordering::Greater) => r#""greater""#,
+        Err(_) => "null",
+    };
+    write!(
+        out,
+        r#"{{"type":"pair","index":{},"ordering":{ordering},"left":{{"start":{},"end":{}}},"right":{{"start":{},"end":{}}},"error":"#,
+        pair.index, pair.left.start, pair.left.end, pair.right.start, pair.right.end,
+    )?;
+    write_error(out, pair.result.err())?;
+    writeln!(out, "}}")
+}
+
+fn write_error(out: &mut impl Write, error: Option<Error>) -> io::Result<()> {
+    let Some(error) = error else {
+        return write!(out, "null");
+    };
+    let kind = match error {
+        Error::TooManyPairs { .. } => "too_many_pairs",
+        Error::LineTooLong { .. } => "line_too_long",
+        Error::TooDeep { .. } => "too_deep",
+        Error::NumberTooLong { .. } => "number_too_long",
+        Error::Syntax { .. } => "syntax",
+    };
+    write!(out, r#"{{"kind":"{kind}","message":"#)?;
+    write_string(out, &error.to_string())?;
+    write!(out, "}}")
+}
+
+/// Writes `s` as a JSON string, with quotes
+fn write_string(out: &mut impl Write, s: &str) -> io::Result<()> {
+    write!(out, "\"")?;
+    for ch in s.chars() {
+        match ch {
+            '"' => write!(out, "\\\"")?,
+            '\\' => write!(out, "\\\\")?,
+            '\n' => write!(out, "\\n")?,
+            '\r' => write!(out, "\\r")?,
+            '\t' => write!(out, "\\t")?,
+            ch if ch < ' ' => write!(out, "\\u{:04x}", ch as u32)?,
+            ch => write!(out, "{ch}")?,
+        }
+    }
+    write!(out, "\"")
+}
@@ -1,12 +1,25 @@
+#![no_std]
+#![forbid(unsafe_code)]
+
+extern crate alloc;
+#[cfg(any(feature = "std", test))]
+extern crate std;
+
+pub mod generator;
 pub mod input_handling_baseline;
+#[cfg(feature = "std")]
+pub mod json_lines;
 pub mod logos_lex;
 pub mod manual_lex;
 pub mod naive;
 pub mod naive_slice;
 pub mod prefix_comp_then_logos_lex;
+pub mod registry;
 mod shared;
 pub mod single_pass_prefix_comp_then_logos_lex;
 
+pub use shared::{limits, res_pool, Error, Pair};
+
 pub const SAMPLE: &str = "[1,1,3,1,1]
 [1,1,5,1,1]
 
@@ -32,10 +45,18 @@ pub const SAMPLE: &str = "[1,1,3,1,1]
 [1,[2,[3,[4,[5,6,0]]]],8,9]";
 
 #[cfg(test)]
+#[allow(clippy::identity_op)]
 mod tests {
+    use crate::generator::{Params, Placement, Preset, Rng};
+    use crate::limits::{Limits, Unbounded};
+    use crate::shared::res_pool::{Alloc, GuardPool, Oversized, ResPool, Retention};
     use crate::{
         logos_lex, manual_lex, naive, naive_slice, prefix_comp_then_logos_lex,
-        single_pass_prefix_comp_then_logos_lex, SAMPLE,
+        single_pass_prefix_comp_then_logos_lex, Error, SAMPLE,
+    };
+    use alloc::{
+        string::{String, ToString},
+        vec::Vec,
     };
     use duplicate::duplicate;
 
@@ -44,8 +65,11 @@ mod tests {
             func name;
             [naive::pooled::day13] [naive_pool];
             [naive::no_pool::day13] [naive_no_pool];
+            [naive::guarded::day13] [naive_guarded];
+            [naive::arena::day13] [naive_arena];
             [naive_slice::no_pool::day13] [naive_slice_no_pool];
             [naive_slice::pooled::day13] [naive_slice_pool];
+            [naive_slice::arena::day13] [naive_slice_arena];
             [manual_lex::day13] [manual_lex_pool];
             [logos_lex::day13] [logos_lex];
             [prefix_comp_then_logos_lex::day13::<16>] [prefix_comp_then_logos_lex];
@@ -56,8 +80,532 @@ mod tests {
             assert_eq!(func(SAMPLE), 13);
             assert_eq!(func(OTHER), 1 + 0 + 5 + 7 + 8 + 10);
             assert_eq!(func("[]\n[]"), 0);
+            assert_eq!(func(CLOSING_RUNS), 2 + 3 + 5 + 7);
+        }
+    }
+    #[test]
+    fn guard_pool_reuses_dropped_items() {
+        let pool = GuardPool::new(Vec::<u8>::new);
+        let mut first = pool.take();
+        first.extend_from_slice(b"123");
+        let buffer = first.as_ptr();
+        drop(first);
+
+        let second = pool.take();
+        assert!(second.is_empty());
+        assert_eq!(second.as_ptr(), buffer);
+    }
+
+    #[test]
+    fn res_pool_keeps_within_retention_limits() {
+        let ten_kb_number = "1029637485".repeat(1000);
+        let new_string = &mut String::new;
+        let mut pool = ResPool::with_retention(
+            new_string,
+            Retention {
+                max_items: 2,
+                max_item_bytes: 64,
+                oversized: Oversized::Drop,
+            },
+        );
+        pool.deposit(ten_kb_number.clone());
+        assert_eq!(pool.held(), 0);
+        for _ in 0..3 {
+            pool.deposit("123".to_string());
+        }
+        assert_eq!(pool.held(), 2);
+        assert!(pool.withdraw().is_empty());
+        pool.trim();
+        assert_eq!(pool.held(), 0);
+
+        let new_string = &mut String::new;
+        let mut pool = ResPool::with_retention(
+            new_string,
+            Retention {
+                oversized: Oversized::Shrink,
+                max_item_bytes: 64,
+                ..Retention::UNBOUNDED
+            },
+        );
+        pool.deposit(ten_kb_number);
+        assert_eq!(pool.held(), 1);
+        assert!(pool.retained_bytes() <= 64);
+
+        let bounded = Retention {
+            max_items: 1,
+            max_item_bytes: 16,
+            oversized: Oversized::Drop,
+        };
+        assert_eq!(
+            naive::pooled::day13_with_retention(OTHER, bounded, bounded),
+            31
+        );
+        assert_eq!(
+            naive_slice::pooled::day13_with_retention(OTHER, bounded),
+            31
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn sync_pool_keeps_within_retention_limits() {
+        use crate::shared::res_pool::SyncPool;
+
+        let ten_kb_number = "1029637485".repeat(1000);
+        // one shard, so every deposit counts against the same limits
+        let shared = SyncPool::with_shards(
+            String::new,
+            1,
+            Retention {
+                max_items: 2,
+                max_item_bytes: 64,
+                oversized: Oversized::Drop,
+            },
+        );
+        let mut pool = &shared;
+        pool.deposit(ten_kb_number.clone());
+        assert_eq!(pool.held(), 0);
+        for _ in 0..3 {
+            pool.deposit("123".to_string());
+        }
+        assert_eq!(pool.held(), 2);
+        assert!(pool.withdraw().is_empty());
+
+        // the limits apply to each shard: two threads keep one item each if they deposit into
+        // different shards, one between them if they share one
+        let shared = SyncPool::with_shards(
+            String::new,
+            2,
+            Retention {
+                oversized: Oversized::Shrink,
+                max_item_bytes: 64,
+                max_items: 1,
+            },
+        );
+        std::thread::scope(|scope| {
+            for _ in 0..2 {
+                scope.spawn(|| {
+                    let mut pool = &shared;
+                    pool.deposit(ten_kb_number.clone());
+                    pool.deposit(ten_kb_number.clone());
+                });
+            }
+        });
+        let pool = &shared;
+        assert!((1..=2).contains(&pool.held()));
+        assert!(pool.retained_bytes() <= 2 * 64);
+
+        let bounded = Retention {
+            max_items: 1,
+            max_item_bytes: 16,
+            oversized: Oversized::Drop,
+        };
+        let pools = naive::SharedPools::with_retention(bounded, bounded);
+        assert_eq!(naive::pooled::day13_shared(OTHER, &pools), 31);
+        assert!(pools.held() <= 2);
+        let pools = naive_slice::SharedPools::with_retention(bounded);
+        assert_eq!(naive_slice::pooled::day13_shared(OTHER, &pools), 31);
+        assert!(pools.held() <= 1);
+    }
+
+    duplicate! {
+        [
+            func name;
+            [naive::pooled::try_day13] [naive_pool_limits];
+            [naive::no_pool::try_day13] [naive_no_pool_limits];
+            [naive::guarded::try_day13] [naive_guarded_limits];
+            [naive::arena::try_day13] [naive_arena_limits];
+            [naive_slice::no_pool::try_day13] [naive_slice_no_pool_limits];
+            [naive_slice::pooled::try_day13] [naive_slice_pool_limits];
+            [naive_slice::arena::try_day13] [naive_slice_arena_limits];
+            [manual_lex::try_day13] [manual_lex_limits];
+            [logos_lex::try_day13] [logos_lex_limits];
+            [prefix_comp_then_logos_lex::try_day13::<16>] [prefix_comp_then_logos_lex_limits];
+            [single_pass_prefix_comp_then_logos_lex::try_day13] [single_pass_prefix_comp_limits];
+        ]
+        #[test]
+        fn name() {
+            assert_eq!(func(SAMPLE, &Unbounded), Ok(13));
+            assert_eq!(func(SAMPLE, &Limits::UNBOUNDED), Ok(13));
+            assert_eq!(func(OTHER, &Unbounded), Ok(1 + 0 + 5 + 7 + 8 + 10));
+            // SAMPLE's largest: 8 pairs, 27 byte lines, 5 levels deep, 1 digit numbers
+            let largest = Limits {
+                max_pairs: 8,
+                max_line_len: 27,
+                max_depth: 5,
+                max_number_len: 1,
+            };
+            assert_eq!(func(SAMPLE, &largest), Ok(13));
+            assert_eq!(
+                func(SAMPLE, &Limits { max_pairs: 7, ..largest }),
+                Err(Error::TooManyPairs { max_pairs: 7 })
+            );
+            assert_eq!(
+                func(SAMPLE, &Limits { max_line_len: 26, ..largest }),
+                Err(Error::LineTooLong { max_line_len: 26 })
+            );
+            assert_eq!(
+                func(SAMPLE, &Limits { max_depth: 4, ..largest }),
+                Err(Error::TooDeep { max_depth: 4 })
+            );
+            assert_eq!(
+                func("[1,[2,10]]\n[1,[2]]", &largest),
+                Err(Error::NumberTooLong { max_number_len: 1 })
+            );
+            // the limits apply to the right line too
+            assert_eq!(
+                func("[1]\n[[[[[[1]]]]]]", &largest),
+                Err(Error::TooDeep { max_depth: 5 })
+            );
+            // before the right line is compared, which may panic on an invalid one
+            assert_eq!(
+                func("[1,2]\n[1,xx]", &Limits { max_line_len: 5, ..largest }),
+                Err(Error::LineTooLong { max_line_len: 5 })
+            );
+        }
+    }
+
+    #[test]
+    fn deep_nesting_does_not_overflow_the_stack() {
+        const DEPTH: usize = 300_000;
+        let nested = |depth| "[".repeat(depth) + &"]".repeat(depth);
+        let input = nested(DEPTH) + "\n" + &nested(DEPTH + 1);
+        for day13 in [
+            naive::no_pool::day13,
+            naive::pooled::day13,
+            naive::guarded::day13,
+            naive::arena::day13,
+            naive_slice::no_pool::day13,
+            naive_slice::pooled::day13,
+            naive_slice::arena::day13,
+        ] {
+            assert_eq!(day13(&input), 1);
         }
+
+        let depth = |max_depth| Limits {
+            max_depth,
+            ..Limits::UNBOUNDED
+        };
+        let too_deep = Err(Error::TooDeep { max_depth: DEPTH });
+        for try_day13 in [
+            naive::no_pool::try_day13,
+            naive::pooled::try_day13,
+            naive::guarded::try_day13,
+            naive::arena::try_day13,
+            naive_slice::no_pool::try_day13,
+            naive_slice::pooled::try_day13,
+            naive_slice::arena::try_day13,
+        ] {
+            assert_eq!(try_day13(&input, &depth(DEPTH + 1)), Ok(1));
+            assert_eq!(try_day13(&input, &depth(DEPTH)), too_deep);
+        }
+    }
+
+    #[test]
+    fn invalid_lines_are_errors() {
+        // (input, byte offset of the error in its line)
+        const INVALID: [(&str, usize); 7] = [
+            ("[1,,2]\n[1]", 3),
+            ("[1]]\n[1]", 3),
+            ("[1,[2]\n[1]", 6),
+            ("[1 2]\n[1]", 3),
+            ("  [a]\n[1]", 3),
+            ("12,3\n[1]", 2),
+            ("[1]\n[[],x]", 4),
+        ];
+        for try_day13 in [
+            naive::no_pool::try_day13,
+            naive::pooled::try_day13,
+            naive::guarded::try_day13,
+            naive::arena::try_day13,
+            naive_slice::no_pool::try_day13,
+            naive_slice::pooled::try_day13,
+            naive_slice::arena::try_day13,
+        ] {
+            for (input, expected_offset) in INVALID {
+                match try_day13(input, &Unbounded) {
+                    Err(Error::Syntax { offset, .. }) => {
+                        assert_eq!(offset, expected_offset, "{input}")
+                    }
+                    other => panic!("{input}: expected a syntax error, got {other:?}"),
+                }
+            }
+            assert_eq!(try_day13(SAMPLE, &Unbounded), Ok(13));
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn json_lines_report_every_pair() {
+        use crate::{json_lines, registry};
+
+        let implementation = registry::find(registry::DEFAULT).unwrap();
+        let mut out = Vec::new();
+        let answer = json_lines::write(implementation, SAMPLE, &Limits::UNBOUNDED, &mut out);
+        assert_eq!(answer.unwrap(), Ok(13));
+        let out = String::from_utf8(out).unwrap();
+        let lines: Vec<_> = out.lines().collect();
+        assert_eq!(lines.len(), 8 + 1);
+        assert_eq!(
+            lines[0],
+            r#"{"type":"pair","index":1,"ordering":"less","left":{"start":0,"end":11},"right":{"start":12,"end":23},"error":null}"#
+        );
+        assert_eq!(
+            lines[2],
+            r#"{"type":"pair","index":3,"ordering":"greater","left":{"start":48,"end":51},"right":{"start":52,"end":61},"error":null}"#
+        );
+        assert_eq!(
+            lines[8],
+            r#"{"type":"summary","implementation":"naive_slice::pooled","pairs":8,"answer":13,"error":null}"#
+        );
+
+        let mut out = Vec::new();
+        let answer = json_lines::write(
+            implementation,
+            "[1]\n[2]\n\n[1,,]\n[2]",
+            &Limits::UNBOUNDED,
+            &mut out,
+        );
+        assert!(matches!(
+            answer.unwrap(),
+            Err(Error::Syntax { offset: 3, .. })
+        ));
+        let out = String::from_utf8(out).unwrap();
+        let lines: Vec<_> = out.lines().collect();
+        assert_eq!(
+            lines[1],
+            r#"{"type":"pair","index":2,"ordering":null,"left":{"start":9,"end":14},"right":{"start":15,"end":18},"error":{"kind":"syntax","message":"expected element before comma (at byte 3 of the line)"}}"#
+        );
+        assert_eq!(
+            lines[2],
+            r#"{"type":"summary","implementation":"naive_slice::pooled","pairs":2,"answer":null,"error":{"kind":"syntax","message":"expected element before comma (at byte 3 of the line)"}}"#
+        );
     }
+
+    #[test]
+    fn every_implementation_reports_the_same_pairs() {
+        let mut expected = Vec::new();
+        let reference =
+            naive::pooled::try_day13_observed(SAMPLE, &Unbounded, |pair| expected.push(pair));
+        assert_eq!(reference, Ok(13));
+        for implementation in crate::registry::IMPLEMENTATIONS {
+            let mut pairs = Vec::new();
+            let answer =
+                (implementation.try_day13_observed)(SAMPLE, &Limits::UNBOUNDED, &mut |pair| {
+                    pairs.push(pair)
+                });
+            assert_eq!(answer, Ok(13), "{}", implementation.name);
+            assert_eq!(pairs, expected, "{}", implementation.name);
+        }
+    }
+
+    #[test]
+    fn generated_inputs_follow_their_params() {
+        let params = Params {
+            seed: 7,
+            pairs: 200,
+            depth: 6..=6,
+            width: 0..=3,
+            number_len: 1..=4,
+            shared_prefix_len: 20,
+            first_difference: Placement::First,
+            equal_percent: 30,
+        };
+        let input = params.generate();
+        assert_eq!(input, params.generate());
+        assert_ne!(
+            input,
+            Params {
+                seed: 8,
+                ..params.clone()
+            }
+            .generate()
+        );
+
+        let limits = Limits {
+            max_depth: 6,
+            max_number_len: 4,
+            ..Limits::UNBOUNDED
+        };
+        let mut equal = 0;
+        let answer = naive_slice::pooled::try_day13_observed(&input, &limits, |pair| {
+            let (left, right) = (&input[pair.left], &input[pair.right]);
+            if left == right {
+                equal += 1;
+            } else {
+                assert_eq!(left[..20], right[..20]);
+            }
+        });
+        assert!(answer.is_ok());
+        assert!((40..80).contains(&equal), "{equal} equal pairs");
+        let too_deep = Limits {
+            max_depth: 5,
+            ..limits
+        };
+        assert_eq!(
+            naive_slice::pooled::try_day13(&input, &too_deep),
+            Err(Error::TooDeep { max_depth: 5 })
+        );
+    }
+
+    #[test]
+    fn every_implementation_agrees_on_generated_inputs() {
+        for seed in 0..30 {
+            let mut rng = Rng::new(seed);
+            let params = Params {
+                seed,
+                pairs: 20,
+                depth: 1..=1 + rng.below(12),
+                width: 0..=rng.below(6),
+                number_len: 1..=1 + rng.below(20),
+                shared_prefix_len: rng.below(40),
+                first_difference: [Placement::First, Placement::Last, Placement::Anywhere]
+                    [rng.below(3)],
+                equal_percent: rng.below(50) as u8,
+            };
+            let input = params.generate();
+            let expected = naive_slice::pooled::day13(&input);
+            for implementation in crate::registry::IMPLEMENTATIONS {
+                assert_eq!(
+                    (implementation.day13)(&input),
+                    expected,
+                    "{} on {params:?}",
+                    implementation.name
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn shuffle_is_a_seeded_permutation() {
+        let shuffled = |seed| {
+            let mut items: Vec<usize> = (0..100).collect();
+            Rng::new(seed).shuffle(&mut items);
+            items
+        };
+        let items = shuffled(1);
+        assert_eq!(items, shuffled(1));
+        assert_ne!(items, shuffled(2));
+        assert_ne!(items, (0..100).collect::<Vec<_>>());
+        let mut sorted = items.clone();
+        sorted.sort_unstable();
+        assert_eq!(sorted, (0..100).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn generator_presets() {
+        assert_eq!(Preset::Sample.generate(), SAMPLE);
+        assert_eq!(
+            naive_slice::pooled::day13(&Preset::SampleRepeated1K.generate()),
+            (0..1000).map(|i| 13 + 4 * 8 * i).sum::<usize>()
+        );
+        for preset in [Preset::LongNumberLastDigit, Preset::LongNumberFirstDigit] {
+            let input = preset.generate();
+            let (left, right) = input.split_once('\n').unwrap();
+            assert_eq!((left.len(), right.len()), (9999, 9999));
+            assert_eq!(naive_slice::pooled::day13(&input), 0);
+        }
+        for preset in Preset::ALL {
+            assert_eq!(Preset::find(preset.name()), Some(preset));
+            assert!(naive_slice::pooled::try_day13(&preset.generate(), &Unbounded).is_ok());
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn instrumented_counts_what_the_pool_reused() {
+        use crate::shared::res_pool::{Instrumented, SyncPool};
+
+        let shared = SyncPool::with_shards(String::new, 2, Retention::UNBOUNDED);
+        let pool = &mut Instrumented::new(&shared);
+        let first = pool.withdraw();
+        pool.deposit(first);
+        let (_, reused) = pool.withdraw_reused();
+        assert!(reused);
+        let (_, reused) = pool.withdraw_reused();
+        assert!(!reused);
+        let stats = pool.stats::<String>();
+        assert_eq!((stats.hits, stats.misses), (1, 2));
+
+        // the same for a single-threaded pool
+        let new_string = &mut String::new;
+        let pool = &mut Instrumented::new(ResPool::new(new_string));
+        let first = pool.withdraw();
+        pool.deposit(first);
+        pool.withdraw();
+        let stats = pool.stats::<String>();
+        assert_eq!((stats.hits, stats.misses, stats.high_water), (1, 1, 1));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn pools_shared_between_threads() {
+        const THREADS: usize = 8;
+        let naive_pools = naive::SharedPools::new();
+        let naive_slice_pools = naive_slice::SharedPools::new();
+        std::thread::scope(|scope| {
+            for _ in 0..THREADS {
+                scope.spawn(|| {
+                    for _ in 0..200 {
+                        assert_eq!(naive::pooled::day13_shared(OTHER, &naive_pools), 31);
+                        assert_eq!(
+                            naive_slice::pooled::day13_shared(OTHER, &naive_slice_pools),
+                            31
+                        );
+                        assert_eq!(naive::pooled::day13_thread_local(SAMPLE), 13);
+                        assert_eq!(naive_slice::pooled::day13_thread_local(SAMPLE), 13);
+                        assert_eq!(naive::guarded::day13_thread_local(SAMPLE), 13);
+                        assert_eq!(naive::arena::day13_thread_local(SAMPLE), 13);
+                        assert_eq!(naive_slice::arena::day13_thread_local(SAMPLE), 13);
+                    }
+                });
+            }
+        });
+
+        // a thread only takes from another's shard when its own is empty, so no shard grows past
+        // what its threads use at once, however many pairs they compare
+        let (_, lists, strings) = naive::pooled::day13_with_stats(OTHER);
+        assert!(naive_pools.held() > 0);
+        assert!(naive_pools.held() <= THREADS * (lists.misses + strings.misses));
+        let (_, lists) = naive_slice::pooled::day13_with_stats(OTHER);
+        assert!(naive_slice_pools.held() > 0);
+        assert!(naive_slice_pools.held() <= THREADS * lists.misses);
+    }
+
+    /// Counterpart of the README's heaptrack comparison, `day13 --stats` prints the same stats
+    #[test]
+    fn pooled_variants_stop_creating_items() {
+        let repeated = {
+            let mut base = SAMPLE.to_string();
+            base.push_str("\n\n");
+            base = base.repeat(1000);
+            base.truncate(base.len() - 2);
+            base
+        };
+
+        let (_, no_pool_lists_once, no_pool_strings_once) =
+            naive::no_pool::day13_with_stats(SAMPLE);
+        let (_, no_pool_lists, no_pool_strings) = naive::no_pool::day13_with_stats(&repeated);
+        let (_, pooled_lists_once, pooled_strings_once) = naive::pooled::day13_with_stats(SAMPLE);
+        let (_, pooled_lists, pooled_strings) = naive::pooled::day13_with_stats(&repeated);
+        assert_eq!(no_pool_lists.misses, 1000 * no_pool_lists_once.misses);
+        assert_eq!(no_pool_strings.misses, 1000 * no_pool_strings_once.misses);
+        // only as many items as the most demanding pair needs at once, however many pairs there are
+        assert_eq!(pooled_lists.misses, pooled_lists_once.misses);
+        assert_eq!(pooled_strings.misses, pooled_strings_once.misses);
+        assert_eq!(pooled_lists.high_water, pooled_lists.misses);
+
+        let (_, no_pool_once) = naive_slice::no_pool::day13_with_stats(SAMPLE);
+        let (_, no_pool) = naive_slice::no_pool::day13_with_stats(&repeated);
+        let (_, pooled_once) = naive_slice::pooled::day13_with_stats(SAMPLE);
+        let (_, pooled) = naive_slice::pooled::day13_with_stats(&repeated);
+        assert_eq!(no_pool.misses, 1000 * no_pool_once.misses);
+        assert_eq!(pooled.misses, pooled_once.misses);
+        assert_eq!(no_pool.hits, 0);
+        assert_eq!(no_pool.retained_bytes, 0);
+        assert!(pooled.retained_bytes > 0);
+    }
+
     const OTHER: &str = "[51246543,3456543,[[23456]]]
 [51246543,3456543,23476]
 
@@ -87,4 +635,27 @@ mod tests {
 
 [[987654321],[123456789],[[987654321]],[[123456789]],987654320]
 [987654321,123456789,987654321,123456789,[[987654321]]]";
+    const CLOSING_RUNS: &str = "[[[[1]]],2]
+[[[1]],1]
+
+[[1],[[2]]]
+[[1],[[2],3]]
+
+[[[]]]
+[[[]],[]]
+
+[[[[]]],1]
+[[[]]]
+
+[[[[5]]]]
+[[[[5]],4]]
+
+[[[[5],4]]]
+[[[[5]]],4]
+
+[1,[[2]],[[[3]]]]
+[[1],[2],[[3]],4]
+
+[[[[[[[[5],4],3],2],1],0],1]]
+[[[[[[[[5],4],3],2],1],0],1]]";
 }
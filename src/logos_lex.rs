@@ -1,13 +1,30 @@
 //! lexer generated with the `logos` crate.
 
-use crate::shared::day13_framework;
+use crate::shared::{day13_framework, day13_framework_within, limits::Bounds, Error, Pair};
+use core::cmp::Ordering;
 use logos::{Lexer, Logos};
-use std::cmp::Ordering;
 
 pub fn day13(input: &str) -> usize {
     day13_framework(input, compare)
 }
 
+/// [day13], but returns an error on input beyond `limits`.
+/// Bounding the depth or number length adds a scan of each line before logos lexes it,
+/// up to about twice the work.
+/// The lines are not validated: invalid packets still panic, or give a meaningless answer.
+pub fn try_day13(input: &str, limits: &impl Bounds) -> Result<usize, Error> {
+    try_day13_observed(input, limits, |_| {})
+}
+
+/// [try_day13], also reporting each pair to `on_pair`
+pub fn try_day13_observed(
+    input: &str,
+    limits: &impl Bounds,
+    on_pair: impl FnMut(Pair),
+) -> Result<usize, Error> {
+    day13_framework_within(input, limits, compare, on_pair)
+}
+
 fn compare(left: &str, right: &str) -> Ordering {
     fn next_comparable_token(lexer: &mut Lexer<Token>) -> Option<(Token, usize)> {
         let mut depth_change = 0;
@@ -19,11 +36,22 @@ fn compare(left: &str, right: &str) -> Ordering {
             }
         }
     }
+    /// Next token after an element, paired with its length (the number of ']'s for
+    /// [Token::RBraces]). Hands out the `pending` ']'s of a partially matched run first.
+    fn next_separator(lexer: &mut Lexer<Token>, pending: &mut usize) -> Option<(Token, usize)> {
+        if *pending > 0 {
+            return Some((Token::RBraces, core::mem::take(pending)));
+        }
+        let token = lexer.next()?.unwrap();
+        Some((token, lexer.span().len()))
+    }
     let mut left = Token::lexer(left);
     let mut left_depth = 0;
+    let mut left_pending = 0;
 
     let mut right = Token::lexer(right);
     let mut right_depth = 0;
+    let mut right_pending = 0;
 
     loop {
         let left_token = next_comparable_token(&mut left);
@@ -55,24 +83,40 @@ fn compare(left: &str, right: &str) -> Ordering {
                 // greater
                 if left_depth != right_depth {
                     let diff = left_depth.abs_diff(right_depth);
-                    let (deeper_chars, deeper_depth, ret_val) = if left_depth < right_depth {
-                        (&mut right, &mut right_depth, Ordering::Less)
-                    } else {
-                        (&mut left, &mut left_depth, Ordering::Greater)
-                    };
-                    for _ in 0..diff {
-                        let Some(token_result) = deeper_chars.next() else {
-                            panic!("line ended before closing all the '['s")
+                    let (deeper_chars, deeper_pending, deeper_depth, ret_val) =
+                        if left_depth < right_depth {
+                            (
+                                &mut right,
+                                &mut right_pending,
+                                &mut right_depth,
+                                Ordering::Less,
+                            )
+                        } else {
+                            (
+                                &mut left,
+                                &mut left_pending,
+                                &mut left_depth,
+                                Ordering::Greater,
+                            )
                         };
-                        let token = token_result.unwrap();
-                        match token {
-                            Token::RBrace => {}
-                            Token::Comma => return ret_val,
-                            Token::LBraces => panic!("'[' immediately after ']' -> expected comma"),
-                            Token::Number => panic!(
+                    let mut to_close = diff;
+                    while to_close > 0 {
+                        match next_separator(deeper_chars, deeper_pending) {
+                            Some((Token::RBraces, closed)) => {
+                                // any surplus ']'s are matched up after this block
+                                let used = closed.min(to_close);
+                                to_close -= used;
+                                *deeper_pending = closed - used;
+                            }
+                            Some((Token::Comma, _)) => return ret_val,
+                            Some((Token::LBraces, _)) => {
+                                panic!("'[' immediately after ']' -> expected comma")
+                            }
+                            Some((Token::Number, _)) => panic!(
                                 "number {} immediately after ']' -> expected comma",
                                 deeper_chars.slice()
                             ),
+                            None => panic!("line ended before closing all the '['s"),
                         }
                     }
                     *deeper_depth -= diff;
@@ -81,19 +125,22 @@ fn compare(left: &str, right: &str) -> Ordering {
 
                 // first item in both lists were equal. Both lists at equal depth now.
             }
-            (Some(Token::Number), Some(Token::RBrace)) => {
+            (Some(Token::Number), Some(Token::RBraces)) => {
                 return Ordering::Greater;
             }
-            (Some(Token::RBrace), Some(Token::Number)) => {
+            (Some(Token::RBraces), Some(Token::Number)) => {
                 return Ordering::Less;
             }
-            (Some(Token::RBrace), Some(Token::RBrace)) => {
+            (Some(Token::RBraces), Some(Token::RBraces)) => {
                 match left_depth.cmp(&right_depth) {
                     Ordering::Equal => {}
                     cmp => return cmp,
                 }
+                // the first ']' of each run closes the empty list, the rest are matched up below
                 left_depth -= 1;
                 right_depth -= 1;
+                left_pending = left.span().len() - 1;
+                right_pending = right.span().len() - 1;
             }
             (Some(_), Some(_)) => unreachable!(""),
         }
@@ -102,19 +149,23 @@ fn compare(left: &str, right: &str) -> Ordering {
 
         // handle following ',' ']' or None
         loop {
-            // only loops when both have RBrace
+            // only loops when both have RBraces
             match (
-                left.next().transpose().unwrap(),
-                right.next().transpose().unwrap(),
+                next_separator(&mut left, &mut left_pending),
+                next_separator(&mut right, &mut right_pending),
             ) {
-                (Some(Token::RBrace), Some(Token::RBrace)) => {
-                    left_depth -= 1;
-                    right_depth -= 1;
+                (Some((Token::RBraces, left_closed)), Some((Token::RBraces, right_closed))) => {
+                    // the longer run's surplus gets compared against the other line's next token
+                    let closed = left_closed.min(right_closed);
+                    left_depth -= closed;
+                    right_depth -= closed;
+                    left_pending = left_closed - closed;
+                    right_pending = right_closed - closed;
                     continue;
                 }
-                (Some(Token::Comma), Some(Token::Comma)) => break, // just skip past them
-                (Some(Token::Comma), Some(Token::RBrace)) => return Ordering::Greater,
-                (Some(Token::RBrace), Some(Token::Comma)) => return Ordering::Less,
+                (Some((Token::Comma, _)), Some((Token::Comma, _))) => break, // just skip past them
+                (Some((Token::Comma, _)), Some((Token::RBraces, _))) => return Ordering::Greater,
+                (Some((Token::RBraces, _)), Some((Token::Comma, _))) => return Ordering::Less,
                 (Some(_), Some(_)) => {
                     panic!("expected comma or closing bracket, got number / open bracket")
                 }
@@ -140,8 +191,8 @@ enum Token {
     #[regex("\\[+")]
     LBraces,
 
-    #[token("]")]
-    RBrace,
+    #[regex("\\]+")]
+    RBraces,
 
     #[regex("[0-9]+")]
     Number,
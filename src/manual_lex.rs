@@ -1,7 +1,7 @@
 //! O(1) space, char-by-char hand-rolled lexer
 
-use crate::shared::day13_framework;
-use std::{
+use crate::shared::{day13_framework, day13_framework_within, limits::Bounds, Error, Pair};
+use core::{
     cmp::Ordering,
     iter::{Enumerate, Peekable},
     str::Bytes,
@@ -11,6 +11,23 @@ pub fn day13(input: &str) -> usize {
     day13_framework(input, compare)
 }
 
+/// [day13], but returns an error on input beyond `limits`.
+/// The lexer doesn't track the depth or number length, so bounding either adds a scan of each line
+/// before it is lexed, up to about twice the work.
+/// The lines are not validated: invalid packets still panic, or give a meaningless answer.
+pub fn try_day13(input: &str, limits: &impl Bounds) -> Result<usize, Error> {
+    try_day13_observed(input, limits, |_| {})
+}
+
+/// [try_day13], also reporting each pair to `on_pair`
+pub fn try_day13_observed(
+    input: &str,
+    limits: &impl Bounds,
+    on_pair: impl FnMut(Pair),
+) -> Result<usize, Error> {
+    day13_framework_within(input, limits, compare, on_pair)
+}
+
 fn compare(left: &str, right: &str) -> Ordering {
     enum Token<'a> {
         LBrace,
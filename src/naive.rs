@@ -1,26 +1,75 @@
 //! Char-by-char parser, collects each line into Vec< Vec | String>
 
-use std::{cmp::Ordering, iter::Peekable};
+use alloc::{string::String, vec::Vec};
+use core::{cmp::Ordering, ops::Range};
 
+#[cfg(feature = "std")]
+use crate::shared::res_pool::{LocalPool, SyncPool};
 use crate::shared::{
-    day13_framework,
-    res_pool::{Alloc, GlobalHeapProxy, ResPool},
+    limits::{Bounds, Unbounded},
+    res_pool::{
+        Alloc, GlobalHeapProxy, GuardPool, Instrumented, PoolStats, Pooled, ResPool, Retention,
+    },
+    tree::{self, Level, View},
+    try_day13_framework, Error, Pair,
 };
 
 /// creates and drop Vecs and Strings each line (global heap).
 pub mod no_pool {
-    use super::{day13_generalized, GlobalHeapProxy};
+    use super::{
+        day13_generalized, try_day13_generalized, Bounds, Element, Error, GlobalHeapProxy,
+        Instrumented, Pair, PoolStats, Stacks, String, Vec,
+    };
 
     pub fn day13(input: &str) -> usize {
         let list_pool = &mut GlobalHeapProxy {};
         let string_pool = &mut GlobalHeapProxy {};
         day13_generalized(input, list_pool, string_pool)
     }
+
+    /// [day13], but returns an error instead of panicking on an invalid line,
+    /// or on input beyond `limits`
+    pub fn try_day13(input: &str, limits: &impl Bounds) -> Result<usize, Error> {
+        try_day13_observed(input, limits, |_| {})
+    }
+
+    /// [try_day13], also reporting each pair to `on_pair`
+    pub fn try_day13_observed(
+        input: &str,
+        limits: &impl Bounds,
+        on_pair: impl FnMut(Pair),
+    ) -> Result<usize, Error> {
+        let list_pool = &mut GlobalHeapProxy {};
+        let string_pool = &mut GlobalHeapProxy {};
+        let stacks = &mut Stacks::default();
+        try_day13_generalized(input, limits, list_pool, string_pool, stacks, on_pair)
+    }
+
+    /// [day13], also returning the usage of the (pass-through) list and string pools.
+    /// Every withdrawal is a miss, so this counts the Vecs and Strings constructed.
+    pub fn day13_with_stats(input: &str) -> (usize, PoolStats, PoolStats) {
+        let list_pool = &mut Instrumented::new(GlobalHeapProxy {});
+        let string_pool = &mut Instrumented::new(GlobalHeapProxy {});
+        let answer = day13_generalized(input, list_pool, string_pool);
+        (
+            answer,
+            list_pool.stats::<Vec<Element>>(),
+            string_pool.stats::<String>(),
+        )
+    }
 }
 
 /// Uses an object pool for the Vecs and Strings
 pub mod pooled {
-    use super::{day13_generalized, ResPool};
+    use super::{
+        day13_generalized, try_day13_generalized, Bounds, Element, Error, Instrumented, Pair,
+        PoolStats, ResPool, Retention, Stacks, String, Vec,
+    };
+    #[cfg(feature = "std")]
+    use {
+        super::{LocalPool, SharedPools, Unbounded},
+        core::cell::RefCell,
+    };
 
     pub fn day13(input: &str) -> usize {
         let new_list = &mut Vec::new;
@@ -31,6 +80,349 @@ pub mod pooled {
 
         day13_generalized(input, list_pool, string_pool)
     }
+
+    /// [day13], but returns an error instead of panicking on an invalid line,
+    /// or on input beyond `limits`
+    pub fn try_day13(input: &str, limits: &impl Bounds) -> Result<usize, Error> {
+        try_day13_observed(input, limits, |_| {})
+    }
+
+    /// [try_day13], also reporting each pair to `on_pair`
+    pub fn try_day13_observed(
+        input: &str,
+        limits: &impl Bounds,
+        on_pair: impl FnMut(Pair),
+    ) -> Result<usize, Error> {
+        let new_list = &mut Vec::new;
+        let list_pool = &mut ResPool::new(new_list);
+
+        let new_string = &mut String::new;
+        let string_pool = &mut ResPool::new(new_string);
+
+        let stacks = &mut Stacks::default();
+        try_day13_generalized(input, limits, list_pool, string_pool, stacks, on_pair)
+    }
+
+    /// [day13], with limits on what the list and string pools keep between pairs
+    pub fn day13_with_retention(input: &str, lists: Retention, strings: Retention) -> usize {
+        let new_list = &mut Vec::new;
+        let list_pool = &mut ResPool::with_retention(new_list, lists);
+
+        let new_string = &mut String::new;
+        let string_pool = &mut ResPool::with_retention(new_string, strings);
+
+        day13_generalized(input, list_pool, string_pool)
+    }
+
+    /// [day13], also returning the usage of the list and string pools
+    pub fn day13_with_stats(input: &str) -> (usize, PoolStats, PoolStats) {
+        let new_list = &mut Vec::new;
+        let list_pool = &mut Instrumented::new(ResPool::new(new_list));
+
+        let new_string = &mut String::new;
+        let string_pool = &mut Instrumented::new(ResPool::new(new_string));
+
+        let answer = day13_generalized(input, list_pool, string_pool);
+        (
+            answer,
+            list_pool.stats::<Vec<Element>>(),
+            string_pool.stats::<String>(),
+        )
+    }
+
+    /// [day13], with the Vecs and Strings taken from pools that other threads may be using
+    /// at the same time
+    #[cfg(feature = "std")]
+    pub fn day13_shared(input: &str, pools: &SharedPools) -> usize {
+        day13_generalized(input, &mut &pools.lists, &mut &pools.strings)
+    }
+
+    /// [day13], with the Vecs and Strings kept in thread locals,
+    /// so later calls on the same thread reuse them
+    #[cfg(feature = "std")]
+    pub fn day13_thread_local(input: &str) -> usize {
+        std::thread_local! {
+            static LISTS: RefCell<Vec<Vec<Element>>> = RefCell::default();
+            static STRINGS: RefCell<Vec<String>> = RefCell::default();
+            static STACKS: RefCell<Stacks> = RefCell::default();
+        }
+        let list_pool = &mut LocalPool::new(&LISTS, Vec::new);
+        let string_pool = &mut LocalPool::new(&STRINGS, String::new);
+        STACKS
+            .with_borrow_mut(|stacks| {
+                try_day13_generalized(input, &Unbounded, list_pool, string_pool, stacks, |_| {})
+            })
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+}
+
+/// List and string pools for [pooled::day13_shared], which can be shared between threads
+#[cfg(feature = "std")]
+pub struct SharedPools {
+    lists: SyncPool<Vec<Element>>,
+    strings: SyncPool<String>,
+}
+
+#[cfg(feature = "std")]
+impl SharedPools {
+    pub fn new() -> Self {
+        SharedPools {
+            lists: SyncPool::new(Vec::new),
+            strings: SyncPool::new(String::new),
+        }
+    }
+
+    /// With limits on what each shard of the pools keeps between pairs,
+    /// like [pooled::day13_with_retention]
+    pub fn with_retention(lists: Retention, strings: Retention) -> Self {
+        SharedPools {
+            lists: SyncPool::with_retention(Vec::new, lists),
+            strings: SyncPool::with_retention(String::new, strings),
+        }
+    }
+
+    /// Number of Vecs and Strings currently stored
+    pub fn held(&self) -> usize {
+        (&self.lists).held() + (&self.strings).held()
+    }
+}
+
+#[cfg(feature = "std")]
+impl Default for SharedPools {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Like [pooled], but the Vecs and Strings are lent out by [GuardPool]s,
+/// and return to them on their own when their guards are dropped (no deposits).
+/// Trees are still taken apart explicitly,
+/// as a guard that holds other guards drops them recursively.
+pub mod guarded {
+    use super::{
+        tree::{self, Level, View},
+        try_day13_framework, Bounds, Error, GuardPool, Ordering, Pair, Pooled, Range, String,
+        Unbounded, Vec,
+    };
+    #[cfg(feature = "std")]
+    use core::cell::RefCell;
+
+    pub fn day13(input: &str) -> usize {
+        try_day13(input, &Unbounded).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// [day13], but returns an error instead of panicking on an invalid line,
+    /// or on input beyond `limits`
+    pub fn try_day13(input: &str, limits: &impl Bounds) -> Result<usize, Error> {
+        try_day13_observed(input, limits, |_| {})
+    }
+
+    /// [try_day13], also reporting each pair to `on_pair`
+    pub fn try_day13_observed(
+        input: &str,
+        limits: &impl Bounds,
+        on_pair: impl FnMut(Pair),
+    ) -> Result<usize, Error> {
+        let pools = &mut Pools::default();
+        let stacks = &mut Stacks::default();
+        try_day13_framework(
+            input,
+            limits,
+            |left, right| compare(left, right, limits, pools, stacks),
+            on_pair,
+        )
+    }
+
+    /// [day13], with the pools and stacks kept in thread locals,
+    /// so later calls on the same thread reuse them
+    #[cfg(feature = "std")]
+    pub fn day13_thread_local(input: &str) -> usize {
+        std::thread_local! {
+            static POOLS: RefCell<Pools> = RefCell::default();
+            static STACKS: RefCell<Stacks> = RefCell::default();
+        }
+        POOLS
+            .with_borrow_mut(|pools| {
+                STACKS.with_borrow_mut(|stacks| {
+                    try_day13_framework(
+                        input,
+                        &Unbounded,
+                        |left, right| compare(left, right, &Unbounded, pools, stacks),
+                        |_| {},
+                    )
+                })
+            })
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    fn compare(
+        left: &str,
+        right: &str,
+        limits: &impl Bounds,
+        pools: &mut Pools,
+        stacks: &mut Stacks,
+    ) -> Result<Ordering, Error> {
+        let mut left = tree::parse(left, limits, pools, &mut stacks.lists)?;
+        let mut right = match tree::parse(right, limits, pools, &mut stacks.lists) {
+            Ok(right) => right,
+            Err(err) => {
+                release(left, &mut stacks.lists);
+                return Err(err);
+            }
+        };
+        let cmp = tree::compare(&mut left, &mut right, &mut stacks.pairs, |l, r| l.cmp(r));
+        release(left, &mut stacks.lists);
+        release(right, &mut stacks.lists);
+        Ok(cmp)
+    }
+
+    struct Pools {
+        lists: GuardPool<Vec<Element>>,
+        strings: GuardPool<String>,
+    }
+
+    impl Default for Pools {
+        fn default() -> Self {
+            Pools {
+                lists: GuardPool::new(Vec::new),
+                strings: GuardPool::new(String::new),
+            }
+        }
+    }
+
+    /// Counterpart of [super::Stacks]
+    #[derive(Default)]
+    struct Stacks {
+        /// lists being parsed or released, innermost last
+        lists: Vec<Pooled<Vec<Element>>>,
+        /// the lists being compared, innermost last
+        pairs: Vec<Level<Element>>,
+    }
+
+    enum Element {
+        Num(Pooled<String>),
+        List(Pooled<Vec<Element>>),
+    }
+
+    impl tree::Element for Element {
+        type Num = str;
+
+        fn view(&self) -> View<'_, Self> {
+            match self {
+                Element::Num(num) => View::Num(num.as_str()),
+                Element::List(items) => View::List(items),
+            }
+        }
+
+        fn items_mut(&mut self) -> Option<&mut Vec<Self>> {
+            match self {
+                Element::Num(_) => None,
+                Element::List(items) => Some(&mut **items),
+            }
+        }
+    }
+
+    impl tree::Builder for Pools {
+        type Node = Element;
+        type Open = Pooled<Vec<Element>>;
+
+        fn number(&mut self, line: &str, digits: Range<usize>) -> Element {
+            let mut num = self.strings.take();
+            num.push_str(&line[digits]);
+            Element::Num(num)
+        }
+
+        fn open(&mut self) -> Pooled<Vec<Element>> {
+            self.lists.take()
+        }
+
+        fn push(&mut self, list: &mut Pooled<Vec<Element>>, item: Element) {
+            list.push(item);
+        }
+
+        fn close(&mut self, list: Pooled<Vec<Element>>) -> Element {
+            Element::List(list)
+        }
+
+        fn abandon(&mut self, open: &mut Vec<Pooled<Vec<Element>>>) {
+            tear_down(open);
+        }
+    }
+
+    /// Drops `element`, returning its Vecs and Strings to their pools
+    fn release(element: Element, open: &mut Vec<Pooled<Vec<Element>>>) {
+        if let Element::List(items) = element {
+            open.push(items);
+            tear_down(open);
+        }
+    }
+
+    /// [release]s the lists on `open` (and their contents) without recursing:
+    /// a list's guard is only dropped once the list is empty
+    fn tear_down(open: &mut Vec<Pooled<Vec<Element>>>) {
+        while let Some(list) = open.last_mut() {
+            match list.pop() {
+                Some(Element::List(items)) => open.push(items),
+                Some(Element::Num(_)) => {}
+                None => drop(open.pop()),
+            }
+        }
+    }
+}
+
+/// Builds both trees of a pair in an arena, which is reset after the comparison instead of being
+/// torn down element by element. The digits are copied into an arena too.
+pub mod arena {
+    use super::{Bounds, Error, Pair, Unbounded};
+    use crate::shared::{
+        arena::{Arena, Trees},
+        try_day13_framework,
+    };
+    #[cfg(feature = "std")]
+    use core::cell::RefCell;
+
+    pub fn day13(input: &str) -> usize {
+        try_day13(input, &Unbounded).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// [day13], but returns an error instead of panicking on an invalid line,
+    /// or on input beyond `limits`
+    pub fn try_day13(input: &str, limits: &impl Bounds) -> Result<usize, Error> {
+        try_day13_observed(input, limits, |_| {})
+    }
+
+    /// [try_day13], also reporting each pair to `on_pair`
+    pub fn try_day13_observed(
+        input: &str,
+        limits: &impl Bounds,
+        on_pair: impl FnMut(Pair),
+    ) -> Result<usize, Error> {
+        let trees = &mut Trees::<Arena<u8>>::default();
+        try_day13_framework(
+            input,
+            limits,
+            |left, right| trees.compare(left, right, limits),
+            on_pair,
+        )
+    }
+
+    /// [day13], with the arenas kept in a thread local, so later calls on the same thread reuse them
+    #[cfg(feature = "std")]
+    pub fn day13_thread_local(input: &str) -> usize {
+        std::thread_local! {
+            static TREES: RefCell<Trees<Arena<u8>>> = RefCell::default();
+        }
+        TREES
+            .with_borrow_mut(|trees| {
+                try_day13_framework(
+                    input,
+                    &Unbounded,
+                    |left, right| trees.compare(left, right, &Unbounded),
+                    |_| {},
+                )
+            })
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
 }
 
 fn day13_generalized(
@@ -38,176 +430,126 @@ fn day13_generalized(
     list_pool: &mut impl Alloc<Vec<Element>>,
     string_pool: &mut impl Alloc<String>,
 ) -> usize {
-    day13_framework(input, |left, right| {
-        let left = Element::parse(left, list_pool, string_pool);
-        let right = Element::parse(right, list_pool, string_pool);
-        let cmp = left.cmp(&right);
-        left.scavenge(list_pool, string_pool);
-        right.scavenge(list_pool, string_pool);
-        cmp
-    })
+    let stacks = &mut Stacks::default();
+    try_day13_generalized(input, &Unbounded, list_pool, string_pool, stacks, |_| {})
+        .unwrap_or_else(|err| panic!("{err}"))
+}
+
+fn try_day13_generalized(
+    input: &str,
+    limits: &impl Bounds,
+    list_pool: &mut impl Alloc<Vec<Element>>,
+    string_pool: &mut impl Alloc<String>,
+    stacks: &mut Stacks,
+    on_pair: impl FnMut(Pair),
+) -> Result<usize, Error> {
+    let compare = |left: &str, right: &str| {
+        let pools = &mut Pools {
+            lists: list_pool,
+            strings: string_pool,
+        };
+        let mut left = tree::parse(left, limits, pools, &mut stacks.lists)?;
+        let mut right = match tree::parse(right, limits, pools, &mut stacks.lists) {
+            Ok(right) => right,
+            Err(err) => {
+                pools.scavenge(left, &mut stacks.lists);
+                return Err(err);
+            }
+        };
+        let cmp = tree::compare(&mut left, &mut right, &mut stacks.pairs, |l, r| l.cmp(r));
+        pools.scavenge(left, &mut stacks.lists);
+        pools.scavenge(right, &mut stacks.lists);
+        Ok(cmp)
+    };
+    try_day13_framework(input, limits, compare, on_pair)
 }
 
-#[derive(PartialEq, Eq, Debug)]
+/// The explicit stacks parsing, comparing and tearing down [Element]s use instead of recursion.
+/// Kept between pairs, so they only allocate until they have grown to the deepest line.
+#[derive(Default)]
+struct Stacks {
+    /// lists being parsed or torn down, innermost last
+    lists: Vec<Vec<Element>>,
+    /// the lists being compared, innermost last
+    pairs: Vec<Level<Element>>,
+}
+
+#[derive(Debug)]
 enum Element {
     Num(String),
     List(Vec<Element>),
 }
 
-impl PartialOrd for Element {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
+impl tree::Element for Element {
+    type Num = str;
+
+    fn view(&self) -> View<'_, Self> {
+        match self {
+            Element::Num(num) => View::Num(num.as_str()),
+            Element::List(items) => View::List(items),
+        }
     }
-}
 
-impl Ord for Element {
-    fn cmp(&self, other: &Self) -> Ordering {
-        match (self, other) {
-            (Element::Num(s), Element::Num(o)) => s.cmp(o),
-            (Element::Num(_), Element::List(o)) => {
-                if o.is_empty() {
-                    return Ordering::Greater;
-                }
-                let first_elem_cmp = self.cmp(&o[0]);
-                if first_elem_cmp.is_eq() && o.len() > 1 {
-                    Ordering::Less
-                } else {
-                    first_elem_cmp
-                }
-            }
-            (Element::List(_), Element::Num(_)) => other.cmp(self).reverse(),
-            (Element::List(s), Element::List(o)) => s.cmp(o),
+    fn items_mut(&mut self) -> Option<&mut Vec<Self>> {
+        match self {
+            Element::Num(_) => None,
+            Element::List(items) => Some(items),
         }
     }
 }
 
-impl Element {
-    fn parse(
-        s: &str,
-        list_pool: &mut impl Alloc<Vec<Element>>,
-        string_pool: &mut impl Alloc<String>,
-    ) -> Element {
-        let s = s.trim();
-        if s.chars().all(|ch| ch.is_ascii_digit()) {
-            return Self::Num(s.to_owned());
-        }
-
-        fn parse_number(
-            chars: &mut Peekable<impl Iterator<Item = char>>,
-            string_pool: &mut impl Alloc<String>,
-        ) -> String {
-            let mut s = string_pool.withdraw();
-            while let Some(d) = chars.next_if(char::is_ascii_digit) {
-                s.push(d);
-            }
-            s
-        }
-        fn consume_until_closing_bracket(
-            chars: &mut Peekable<impl Iterator<Item = char>>,
-            list_pool: &mut impl Alloc<Vec<Element>>,
-            string_pool: &mut impl Alloc<String>,
-        ) -> Vec<Element> {
-            let mut vec = list_pool.withdraw();
-            loop {
-                match chars
-                    .peek()
-                    .expect("expected a closing brace, but reached end of input")
-                {
-                    ']' => {
-                        chars.next();
-                        return vec;
-                    }
-                    '0'..='9' => {
-                        vec.push(Element::Num(parse_number(chars, string_pool)));
-                    }
-                    '[' => {
-                        let _ = chars.next();
-                        vec.push(Element::List(consume_until_closing_bracket(
-                            chars,
-                            list_pool,
-                            string_pool,
-                        )));
-                    }
-                    ',' => panic!("expected element before comma"),
-                    ' ' => continue,
-                    char => panic!("invalid character `{char}`"),
-                }
-                loop {
-                    match chars
-                        .peek()
-                        .expect("expected a closing brace or comma after element")
-                    {
-                        ' ' => continue,
-                        ',' => {
-                            chars.next();
-                            break;
-                        }
-                        ']' => break,
-                        char => panic!("invalid character `{char}`"),
-                    }
-                }
-            }
-        }
+/// Builds [Element]s from the pools' Vecs and Strings, and hands them back
+struct Pools<'p, L, S> {
+    lists: &'p mut L,
+    strings: &'p mut S,
+}
 
-        let mut chars = s.chars().peekable();
-
-        match chars.peek().expect("empty line!") {
-            '[' => {
-                let _ = chars.next();
-                let ele = Element::List(consume_until_closing_bracket(
-                    &mut chars,
-                    list_pool,
-                    string_pool,
-                ));
-                match chars.next() {
-                    Some(',') => {
-                        panic!("unexpected comma (top-level needs to be a list, with '[' and ']')")
-                    }
-                    Some(']') => {
-                        panic!("unexpected ']' (duplicate closing ']', or missing opening '['?")
-                    }
-                    Some(ch) => {
-                        panic!("unexpected character after complete number: `{ch}`");
-                    }
-                    _ => (),
-                }
-                ele
-            }
-            '0'..='9' => {
-                let ele = Element::Num(parse_number(&mut chars, string_pool));
-                match chars.next() {
-                    Some(',') => {
-                        panic!("unexpected comma (top-level needs to be a list, with '[' and ']')")
-                    }
-                    Some(']') => panic!("unexpected ']' (did you forget the opening '['?"),
-                    Some(ch) => {
-                        panic!("unexpected character after complete number: `{ch}`");
-                    }
-                    _ => (),
-                }
-                ele
+impl<L: Alloc<Vec<Element>>, S: Alloc<String>> tree::Builder for Pools<'_, L, S> {
+    type Node = Element;
+    type Open = Vec<Element>;
+
+    fn number(&mut self, line: &str, digits: Range<usize>) -> Element {
+        let mut num = self.strings.withdraw();
+        num.push_str(&line[digits]);
+        Element::Num(num)
+    }
+
+    fn open(&mut self) -> Vec<Element> {
+        self.lists.withdraw()
+    }
+
+    fn push(&mut self, list: &mut Vec<Element>, item: Element) {
+        list.push(item);
+    }
+
+    fn close(&mut self, list: Vec<Element>) -> Element {
+        Element::List(list)
+    }
+
+    fn abandon(&mut self, open: &mut Vec<Vec<Element>>) {
+        self.tear_down(open);
+    }
+}
+
+impl<L: Alloc<Vec<Element>>, S: Alloc<String>> Pools<'_, L, S> {
+    /// tears down `element` and returns its resources (Vecs, Strings) to the pools
+    fn scavenge(&mut self, element: Element, open: &mut Vec<Vec<Element>>) {
+        match element {
+            Element::List(items) => {
+                open.push(items);
+                self.tear_down(open);
             }
-            ' ' => unreachable!(),
-            ch => panic!("invalid character `{ch}`"),
+            Element::Num(str) => self.strings.deposit(str),
         }
     }
 
-    /// tears down this object and returns resources (Vecs, Strings) to the given pools)
-    fn scavenge(
-        self,
-        list_pool: &mut impl Alloc<Vec<Element>>,
-        string_pool: &mut impl Alloc<String>,
-    ) {
-        match self {
-            Self::List(mut items) => {
-                for ele in items.drain(..) {
-                    ele.scavenge(list_pool, string_pool);
-                }
-                list_pool.deposit(items);
-            }
-            Element::Num(mut str) => {
-                str.truncate(0);
-                string_pool.deposit(str);
+    /// [scavenge](Pools::scavenge)s the lists on `open` (and their contents) without recursing
+    fn tear_down(&mut self, open: &mut Vec<Vec<Element>>) {
+        while let Some(list) = open.last_mut() {
+            match list.pop() {
+                Some(Element::List(items)) => open.push(items),
+                Some(Element::Num(str)) => self.strings.deposit(str),
+                None => self.lists.deposit(open.pop().unwrap()),
             }
         }
     }
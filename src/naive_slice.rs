@@ -1,29 +1,69 @@
-//! Like [crate::naive], but uses &str instead of String
+//! Like [crate::naive], but numbers are spans of the line instead of Strings
 
-use std::{
-    cmp::Ordering,
-    iter::Peekable,
-    mem::{align_of, forget, size_of},
-    str::CharIndices,
-};
+use alloc::vec::Vec;
+use core::ops::Range;
 
+#[cfg(feature = "std")]
+use crate::shared::res_pool::{Retention, SyncPool};
 use crate::shared::{
-    day13_framework,
+    arena::Span,
+    limits::{Bounds, Unbounded},
     res_pool::{self, Alloc},
+    tree::{self, Level, View},
+    try_day13_framework, Error, Pair,
 };
 
 /// Creates and drops Vecs each line.
 pub mod no_pool {
-    use super::{day13_generalized, res_pool::GlobalHeapProxy};
+    use super::{
+        day13_generalized,
+        res_pool::{GlobalHeapProxy, Instrumented, PoolStats},
+        try_day13_generalized, Bounds, Element, Error, Pair, Stacks, Vec,
+    };
 
     pub fn day13(input: &str) -> usize {
         let list_pool = &mut GlobalHeapProxy {};
         day13_generalized(input, list_pool)
     }
+
+    /// [day13], but returns an error instead of panicking on an invalid line,
+    /// or on input beyond `limits`
+    pub fn try_day13(input: &str, limits: &impl Bounds) -> Result<usize, Error> {
+        try_day13_observed(input, limits, |_| {})
+    }
+
+    /// [try_day13], also reporting each pair to `on_pair`
+    pub fn try_day13_observed(
+        input: &str,
+        limits: &impl Bounds,
+        on_pair: impl FnMut(Pair),
+    ) -> Result<usize, Error> {
+        let list_pool = &mut GlobalHeapProxy {};
+        let stacks = &mut Stacks::default();
+        try_day13_generalized(input, limits, list_pool, stacks, on_pair)
+    }
+
+    /// [day13], also returning the usage of the (pass-through) list pool.
+    /// Every withdrawal is a miss, so this counts the Vecs constructed.
+    pub fn day13_with_stats(input: &str) -> (usize, PoolStats) {
+        let list_pool = &mut Instrumented::new(GlobalHeapProxy {});
+        let answer = day13_generalized(input, list_pool);
+        (answer, list_pool.stats::<Vec<Element>>())
+    }
 }
+
 /// Uses an object pool for the Vecs.
 pub mod pooled {
-    use super::{day13_generalized, res_pool::ResPool};
+    use super::{
+        day13_generalized,
+        res_pool::{Instrumented, PoolStats, ResPool, Retention},
+        try_day13_generalized, Bounds, Element, Error, Pair, Stacks, Vec,
+    };
+    #[cfg(feature = "std")]
+    use {
+        super::{res_pool::LocalPool, SharedPools, Unbounded},
+        core::cell::RefCell,
+    };
 
     pub fn day13(input: &str) -> usize {
         let new_list = &mut Vec::new;
@@ -31,179 +71,279 @@ pub mod pooled {
 
         day13_generalized(input, list_pool)
     }
+
+    /// [day13], but returns an error instead of panicking on an invalid line,
+    /// or on input beyond `limits`
+    pub fn try_day13(input: &str, limits: &impl Bounds) -> Result<usize, Error> {
+        try_day13_observed(input, limits, |_| {})
+    }
+
+    /// [try_day13], also reporting each pair to `on_pair`
+    pub fn try_day13_observed(
+        input: &str,
+        limits: &impl Bounds,
+        on_pair: impl FnMut(Pair),
+    ) -> Result<usize, Error> {
+        let new_list = &mut Vec::new;
+        let list_pool = &mut ResPool::new(new_list);
+
+        let stacks = &mut Stacks::default();
+        try_day13_generalized(input, limits, list_pool, stacks, on_pair)
+    }
+
+    /// [day13], with limits on what the list pool keeps between pairs
+    pub fn day13_with_retention(input: &str, lists: Retention) -> usize {
+        let new_list = &mut Vec::new;
+        let list_pool = &mut ResPool::with_retention(new_list, lists);
+
+        day13_generalized(input, list_pool)
+    }
+
+    /// [day13], also returning the usage of the list pool
+    pub fn day13_with_stats(input: &str) -> (usize, PoolStats) {
+        let new_list = &mut Vec::new;
+        let list_pool = &mut Instrumented::new(ResPool::new(new_list));
+
+        let answer = day13_generalized(input, list_pool);
+        (answer, list_pool.stats::<Vec<Element>>())
+    }
+
+    /// [day13], with the Vecs taken from a pool that other threads may be using at the same time
+    #[cfg(feature = "std")]
+    pub fn day13_shared(input: &str, pools: &SharedPools) -> usize {
+        day13_generalized(input, &mut &pools.lists)
+    }
+
+    /// [day13], with the Vecs kept in a thread local, so later calls on the same thread reuse them
+    #[cfg(feature = "std")]
+    pub fn day13_thread_local(input: &str) -> usize {
+        std::thread_local! {
+            static LISTS: RefCell<Vec<Vec<Element>>> = RefCell::default();
+            static STACKS: RefCell<Stacks> = RefCell::default();
+        }
+        let list_pool = &mut LocalPool::new(&LISTS, Vec::new);
+        STACKS
+            .with_borrow_mut(|stacks| {
+                try_day13_generalized(input, &Unbounded, list_pool, stacks, |_| {})
+            })
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
 }
 
-fn day13_generalized<'a>(input: &str, list_pool: &mut impl Alloc<Vec<Element<'a>>>) -> usize {
-    day13_framework(input, |left, right| {
-        let left = Element::parse(left, list_pool);
-        let right = Element::parse(right, list_pool);
-        let cmp = left.cmp(&right);
-        left.scavenge(list_pool);
-        right.scavenge(list_pool);
-        cmp
-    })
+/// List pool for [pooled::day13_shared], which can be shared between threads
+#[cfg(feature = "std")]
+pub struct SharedPools {
+    lists: SyncPool<Vec<Element>>,
 }
 
-#[derive(PartialEq, Eq, Debug)]
-enum Element<'a> {
-    Num(&'a str),
-    List(Vec<Element<'a>>),
+#[cfg(feature = "std")]
+impl SharedPools {
+    pub fn new() -> Self {
+        SharedPools {
+            lists: SyncPool::new(Vec::new),
+        }
+    }
+
+    /// With limits on what each shard of the pool keeps between pairs,
+    /// like [pooled::day13_with_retention]
+    pub fn with_retention(lists: Retention) -> Self {
+        SharedPools {
+            lists: SyncPool::with_retention(Vec::new, lists),
+        }
+    }
+
+    /// Number of Vecs currently stored
+    pub fn held(&self) -> usize {
+        (&self.lists).held()
+    }
 }
 
-impl<'a> PartialOrd for Element<'a> {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
+#[cfg(feature = "std")]
+impl Default for SharedPools {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
-impl<'a> Ord for Element<'a> {
-    fn cmp(&self, other: &Self) -> Ordering {
-        match (self, other) {
-            (Element::Num(s), Element::Num(o)) => s.cmp(o),
-            (Element::Num(_), Element::List(o)) => {
-                if o.is_empty() {
-                    return Ordering::Greater;
-                }
-                let first_elem_cmp = self.cmp(&o[0]);
-                if first_elem_cmp.is_eq() && o.len() > 1 {
-                    Ordering::Less
-                } else {
-                    first_elem_cmp
-                }
-            }
-            (Element::List(_), Element::Num(_)) => other.cmp(self).reverse(),
-            (Element::List(s), Element::List(o)) => s.cmp(o),
+/// Builds both trees of a pair in an arena, which is reset after the comparison instead of being
+/// torn down element by element. The numbers are located in the line,
+/// so the nodes don't borrow it, and the arenas are kept from one pair to the next.
+pub mod arena {
+    use super::{Bounds, Error, Pair, Unbounded};
+    use crate::shared::{
+        arena::{InLine, Trees},
+        try_day13_framework,
+    };
+    #[cfg(feature = "std")]
+    use core::cell::RefCell;
+
+    pub fn day13(input: &str) -> usize {
+        try_day13(input, &Unbounded).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// [day13], but returns an error instead of panicking on an invalid line,
+    /// or on input beyond `limits`
+    pub fn try_day13(input: &str, limits: &impl Bounds) -> Result<usize, Error> {
+        try_day13_observed(input, limits, |_| {})
+    }
+
+    /// [try_day13], also reporting each pair to `on_pair`
+    pub fn try_day13_observed(
+        input: &str,
+        limits: &impl Bounds,
+        on_pair: impl FnMut(Pair),
+    ) -> Result<usize, Error> {
+        let trees = &mut Trees::<InLine>::default();
+        try_day13_framework(
+            input,
+            limits,
+            |left, right| trees.compare(left, right, limits),
+            on_pair,
+        )
+    }
+
+    /// [day13], with the arenas kept in a thread local, so later calls on the same thread reuse them
+    #[cfg(feature = "std")]
+    pub fn day13_thread_local(input: &str) -> usize {
+        std::thread_local! {
+            static TREES: RefCell<Trees<InLine>> = RefCell::default();
         }
+        TREES
+            .with_borrow_mut(|trees| {
+                try_day13_framework(
+                    input,
+                    &Unbounded,
+                    |left, right| trees.compare(left, right, &Unbounded),
+                    |_| {},
+                )
+            })
+            .unwrap_or_else(|err| panic!("{err}"))
     }
 }
 
-impl<'a> Element<'a> {
-    fn parse<'pool>(s: &'a str, list_pool: &mut impl Alloc<Vec<Element<'pool>>>) -> Element<'a> {
-        let s = s.trim();
-        if s.chars().all(|ch| ch.is_ascii_digit()) {
-            return Self::Num(s);
-        }
+fn day13_generalized(input: &str, list_pool: &mut impl Alloc<Vec<Element>>) -> usize {
+    let stacks = &mut Stacks::default();
+    try_day13_generalized(input, &Unbounded, list_pool, stacks, |_| {})
+        .unwrap_or_else(|err| panic!("{err}"))
+}
 
-        fn parse_number<'a>(chars: &mut Peekable<CharIndices>, source: &'a str) -> &'a str {
-            let start = chars.next().unwrap().0;
-            let mut end = start;
-            while let Some((idx, _)) = chars.next_if(|(_, c)| c.is_ascii_digit()) {
-                end = idx;
-            }
-            source.split_at(end + 1).0.split_at(start).1
-        }
-        fn consume_until_closing_bracket<'b, 'source>(
-            chars: &mut Peekable<CharIndices>,
-            source: &'source str,
-            list_pool: &mut impl Alloc<Vec<Element<'b>>>,
-        ) -> Vec<Element<'source>> {
-            let mut vec = launder(list_pool.withdraw());
-            loop {
-                match chars
-                    .peek()
-                    .expect("expected a closing brace, but reached end of input")
-                    .1
-                {
-                    ']' => {
-                        chars.next();
-                        return vec;
-                    }
-                    '0'..='9' => {
-                        vec.push(Element::Num(parse_number(chars, source)));
-                    }
-                    '[' => {
-                        let _ = chars.next();
-                        vec.push(Element::List(consume_until_closing_bracket(
-                            chars, source, list_pool,
-                        )));
-                    }
-                    ',' => panic!("expected element before comma"),
-                    ' ' => continue,
-                    char => panic!("invalid character `{char}`"),
-                }
-                loop {
-                    match chars
-                        .peek()
-                        .expect("expected a closing brace or comma after element")
-                        .1
-                    {
-                        ' ' => continue,
-                        ',' => {
-                            chars.next();
-                            break;
-                        }
-                        ']' => break,
-                        char => panic!("invalid character `{char}`"),
-                    }
-                }
+fn try_day13_generalized(
+    input: &str,
+    limits: &impl Bounds,
+    list_pool: &mut impl Alloc<Vec<Element>>,
+    stacks: &mut Stacks,
+    on_pair: impl FnMut(Pair),
+) -> Result<usize, Error> {
+    let compare = |left: &str, right: &str| {
+        let pools = &mut Pools { lists: list_pool };
+        let mut left_tree = tree::parse(left, limits, pools, &mut stacks.lists)?;
+        let mut right_tree = match tree::parse(right, limits, pools, &mut stacks.lists) {
+            Ok(right_tree) => right_tree,
+            Err(err) => {
+                pools.scavenge(left_tree, &mut stacks.lists);
+                return Err(err);
             }
-        }
+        };
+        let cmp = tree::compare(
+            &mut left_tree,
+            &mut right_tree,
+            &mut stacks.pairs,
+            |l: &Span, r: &Span| left[l.start..l.end].cmp(&right[r.start..r.end]),
+        );
+        pools.scavenge(left_tree, &mut stacks.lists);
+        pools.scavenge(right_tree, &mut stacks.lists);
+        Ok(cmp)
+    };
+    try_day13_framework(input, limits, compare, on_pair)
+}
 
-        let mut chars = s.char_indices().peekable();
-
-        match chars.peek().expect("empty line!").1 {
-            '[' => {
-                let _ = chars.next();
-                let ele = Element::List(consume_until_closing_bracket(&mut chars, s, list_pool));
-                match chars.next().map(|a| a.1) {
-                    Some(',') => {
-                        panic!("unexpected comma (top-level needs to be a list, with '[' and ']')")
-                    }
-                    Some(']') => {
-                        panic!("unexpected ']' (duplicate closing ']', or missing opening '['?")
-                    }
-                    Some(ch) => {
-                        panic!("unexpected character after complete number: `{ch}`");
-                    }
-                    _ => (),
-                }
-                ele
-            }
-            '0'..='9' => {
-                let ele = Element::Num(parse_number(&mut chars, s));
-                match chars.next().map(|a| a.1) {
-                    Some(',') => {
-                        panic!("unexpected comma (top-level needs to be a list, with '[' and ']')")
-                    }
-                    Some(']') => panic!("unexpected ']' (did you forget the opening '['?"),
-                    Some(ch) => {
-                        panic!("unexpected character after complete number: `{ch}`");
-                    }
-                    _ => (),
-                }
-                ele
-            }
-            ' ' => unreachable!(),
-            ch => panic!("invalid character `{ch}`"),
+/// The explicit stacks parsing, comparing and tearing down [Element]s use instead of recursion.
+/// Kept between pairs, so they only allocate until they have grown to the deepest line.
+#[derive(Default)]
+struct Stacks {
+    /// lists being parsed or torn down, innermost last
+    lists: Vec<Vec<Element>>,
+    /// the lists being compared, innermost last
+    pairs: Vec<Level<Element>>,
+}
+
+/// Numbers are located in the line the element was parsed from, so the elements don't borrow
+/// the line, and the pooled Vecs can outlive it.
+#[derive(Debug)]
+enum Element {
+    /// digits in the source line
+    Num(Span),
+    List(Vec<Element>),
+}
+
+impl tree::Element for Element {
+    type Num = Span;
+
+    fn view(&self) -> View<'_, Self> {
+        match self {
+            Element::Num(digits) => View::Num(digits),
+            Element::List(items) => View::List(items),
         }
     }
 
-    /// tears down this object and returns resources (Vecs, Strings) to the given pools)
-    fn scavenge<'b>(self, list_pool: &mut impl Alloc<Vec<Element<'b>>>) {
+    fn items_mut(&mut self) -> Option<&mut Vec<Self>> {
         match self {
-            Self::List(mut items) => {
-                for ele in items.drain(..) {
-                    ele.scavenge(list_pool);
-                }
-                let items = launder(items);
-                list_pool.deposit(items);
-            }
-            Element::Num(_) => {}
+            Element::Num(_) => None,
+            Element::List(items) => Some(items),
         }
     }
 }
 
-/// Intended use: ignore lifetime of Vec<Element<'a>> when vec is empty before *and* after
-/// borrowing
-fn launder<Old, New>(mut old: Vec<Old>) -> Vec<New> {
-    assert!(old.is_empty());
-    assert_eq!(align_of::<Old>(), align_of::<New>());
-    assert_eq!(size_of::<Old>(), size_of::<New>());
-    unsafe {
-        let capacity = old.capacity();
-        let len = old.len();
-        let ptr = old.as_mut_ptr();
-        forget(old);
-        Vec::from_raw_parts(ptr as *mut New, len, capacity)
+/// Builds [Element]s from the pool's Vecs, and hands them back
+struct Pools<'p, L> {
+    lists: &'p mut L,
+}
+
+impl<L: Alloc<Vec<Element>>> tree::Builder for Pools<'_, L> {
+    type Node = Element;
+    type Open = Vec<Element>;
+
+    fn number(&mut self, _: &str, digits: Range<usize>) -> Element {
+        Element::Num(Span {
+            start: digits.start,
+            end: digits.end,
+        })
+    }
+
+    fn open(&mut self) -> Vec<Element> {
+        self.lists.withdraw()
+    }
+
+    fn push(&mut self, list: &mut Vec<Element>, item: Element) {
+        list.push(item);
+    }
+
+    fn close(&mut self, list: Vec<Element>) -> Element {
+        Element::List(list)
+    }
+
+    fn abandon(&mut self, open: &mut Vec<Vec<Element>>) {
+        self.tear_down(open);
+    }
+}
+
+impl<L: Alloc<Vec<Element>>> Pools<'_, L> {
+    /// tears down `element` and returns its resources (Vecs) to the pool
+    fn scavenge(&mut self, element: Element, open: &mut Vec<Vec<Element>>) {
+        if let Element::List(items) = element {
+            open.push(items);
+            self.tear_down(open);
+        }
+    }
+
+    /// [scavenge](Pools::scavenge)s the lists on `open` (and their contents) without recursing
+    fn tear_down(&mut self, open: &mut Vec<Vec<Element>>) {
+        while let Some(list) = open.last_mut() {
+            match list.pop() {
+                Some(Element::List(items)) => open.push(items),
+                Some(Element::Num(_)) => {}
+                None => self.lists.deposit(open.pop().unwrap()),
+            }
+        }
     }
 }
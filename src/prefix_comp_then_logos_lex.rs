@@ -1,13 +1,30 @@
 //! skip common prefix in inputs, then lex with logos until decision made. *Does not fully validate input*  
 
-use crate::shared::day13_framework;
+use crate::shared::{day13_framework, day13_framework_within, limits::Bounds, Error, Pair};
+use core::{cmp::Ordering, iter};
 use logos::{Lexer, Logos};
-use std::{cmp::Ordering, iter};
 
 pub fn day13<const N: usize>(input: &str) -> usize {
     day13_framework(input, compare::<N>)
 }
 
+/// [day13], but returns an error on input beyond `limits`.
+/// The common prefix is skipped without being lexed, so bounding the depth or number length
+/// adds a full scan of both lines before they are compared.
+/// The lines are not validated: invalid packets still panic, or give a meaningless answer.
+pub fn try_day13<const N: usize>(input: &str, limits: &impl Bounds) -> Result<usize, Error> {
+    try_day13_observed::<N>(input, limits, |_| {})
+}
+
+/// [try_day13], also reporting each pair to `on_pair`
+pub fn try_day13_observed<const N: usize>(
+    input: &str,
+    limits: &impl Bounds,
+    on_pair: impl FnMut(Pair),
+) -> Result<usize, Error> {
+    day13_framework_within(input, limits, compare::<N>, on_pair)
+}
+
 fn compare<const N: usize>(left: &str, right: &str) -> Ordering {
     // nkkarpov - https://users.rust-lang.org/t/how-to-find-common-prefix-of-two-byte-slices-effectively/25815/4
     /// returns length of common prefix / index of first mismatch
@@ -85,7 +102,13 @@ fn compare<const N: usize>(left: &str, right: &str) -> Ordering {
                 });
             let deeper_number = match deeper_first_comparable_token {
                 Token::Comma | Token::LBrace => unreachable!(),
-                Token::RBrace => return Ordering::Greater,
+                // the list's first item is an empty list, which is less than the number
+                Token::RBrace => {
+                    return match which_is_list {
+                        WhichIsList::Left => Ordering::Less,
+                        WhichIsList::Right => Ordering::Greater,
+                    }
+                }
                 Token::Number => deeper_lexer.slice(),
             };
 
@@ -0,0 +1,61 @@
+//! Every part A implementation, by name. Used by the `day13` binary.
+//!
+//! The names are the benchmark ids, e.g. `naive_slice::pooled`, `logos_lex`, or
+//! `prefix_comp_then_logos_lex16`. [input_handling_baseline](crate::input_handling_baseline)
+//! is left out, as it doesn't actually compare the packets.
+
+use crate::{
+    limits::Limits, logos_lex, manual_lex, naive, naive_slice, prefix_comp_then_logos_lex,
+    single_pass_prefix_comp_then_logos_lex, Error, Pair,
+};
+
+/// A module's `try_day13_observed`, with a `dyn` observer
+pub type TryDay13Observed = fn(&str, &Limits, &mut dyn FnMut(Pair)) -> Result<usize, Error>;
+
+pub struct Implementation {
+    pub name: &'static str,
+    pub day13: fn(&str) -> usize,
+    pub try_day13: fn(&str, &Limits) -> Result<usize, Error>,
+    pub try_day13_observed: TryDay13Observed,
+    /// Whether `try_day13` returns an [Error::Syntax] for every invalid line.
+    /// The others may panic, or give a meaningless answer.
+    pub validates: bool,
+}
+
+macro_rules! implementations {
+    ($($name:literal => $($module:ident)::+ $(::<$n:literal>)?, validates: $validates:literal;)*) => {
+        &[$(Implementation {
+            name: $name,
+            day13: $($module)::+::day13 $(::<$n>)?,
+            try_day13: $($module)::+::try_day13 $(::<$n>)?,
+            try_day13_observed: |input, limits, on_pair| {
+                $($module)::+::try_day13_observed $(::<$n>)?(input, limits, on_pair)
+            },
+            validates: $validates,
+        },)*]
+    };
+}
+
+pub const IMPLEMENTATIONS: &[Implementation] = implementations! {
+    "naive::pooled" => naive::pooled, validates: true;
+    "naive::no_pool" => naive::no_pool, validates: true;
+    "naive::guarded" => naive::guarded, validates: true;
+    "naive::arena" => naive::arena, validates: true;
+    "naive_slice::pooled" => naive_slice::pooled, validates: true;
+    "naive_slice::no_pool" => naive_slice::no_pool, validates: true;
+    "naive_slice::arena" => naive_slice::arena, validates: true;
+    "manual_lex" => manual_lex, validates: false;
+    "logos_lex" => logos_lex, validates: false;
+    "prefix_comp_then_logos_lex16" => prefix_comp_then_logos_lex::<16>, validates: false;
+    "prefix_comp_then_logos_lex128" => prefix_comp_then_logos_lex::<128>, validates: false;
+    "single_pass_prefix_comp_then_logos_lex" => single_pass_prefix_comp_then_logos_lex, validates: false;
+};
+
+/// The fastest implementation that [validates](Implementation::validates) its input
+pub const DEFAULT: &str = "naive_slice::pooled";
+
+pub fn find(name: &str) -> Option<&'static Implementation> {
+    IMPLEMENTATIONS
+        .iter()
+        .find(|implementation| implementation.name == name)
+}
@@ -1,5 +1,6 @@
 //! Dependency for day13 implementations.
-use std::cmp::Ordering;
+use core::{cmp::Ordering, fmt, ops::Range};
+use limits::Bounds;
 
 /// Outline of a solution - extracts pairs and passes them to the given line comparator
 #[inline(always)]
@@ -10,7 +11,9 @@ pub(crate) fn day13_framework(
     let mut count = 0;
     let mut idx = 1;
     while !input.is_empty() {
-        let Some((left, rem)) = input.split_once('\n') else {break};
+        let Some((left, rem)) = input.split_once('\n') else {
+            break;
+        };
         let (right, rem) = rem.split_once('\n').unwrap_or((rem, ""));
 
         if line_comparator(left, right).is_lt() {
@@ -23,13 +26,299 @@ pub(crate) fn day13_framework(
     count
 }
 
+/// [day13_framework] for comparators that can reject a pair; stops at the first error.
+/// Enforces the pair count and line length `limits`, the comparator is responsible for the rest.
+/// Every pair compared (or rejected) is reported to `on_pair`.
+#[inline(always)]
+pub(crate) fn try_day13_framework(
+    source: &str,
+    limits: &impl Bounds,
+    mut line_comparator: impl FnMut(&str, &str) -> Result<Ordering, Error>,
+    mut on_pair: impl FnMut(Pair),
+) -> Result<usize, Error> {
+    let mut input = source;
+    let mut count = 0;
+    let mut idx = 1;
+    while !input.is_empty() {
+        let Some((left, rem)) = input.split_once('\n') else {
+            break;
+        };
+        let (right, rem) = rem.split_once('\n').unwrap_or((rem, ""));
+
+        let result = limits
+            .check_pairs(idx)
+            .and_then(|()| limits.check_line_len(left))
+            .and_then(|()| limits.check_line_len(right))
+            .and_then(|()| line_comparator(left, right));
+        on_pair(Pair {
+            index: idx,
+            left: span_in(source, left),
+            right: span_in(source, right),
+            result,
+        });
+        if result?.is_lt() {
+            count += idx;
+        }
+
+        input = rem.trim_start_matches('\n');
+        idx += 1;
+    }
+    Ok(count)
+}
+
+/// [try_day13_framework] for comparators that may not look at (or check) every byte of a line:
+/// both lines are scanned for the depth and number length `limits` before they are compared.
+/// The comparator then reads the lines again, so with either bounded, a comparison that reads
+/// the whole line costs up to about twice as much. The prefix comparisons skip bytes without
+/// lexing them, so the checks can't be folded into every comparator.
+#[inline(always)]
+pub(crate) fn day13_framework_within(
+    input: &str,
+    limits: &impl Bounds,
+    mut line_comparator: impl FnMut(&str, &str) -> Ordering,
+    on_pair: impl FnMut(Pair),
+) -> Result<usize, Error> {
+    try_day13_framework(
+        input,
+        limits,
+        |left, right| {
+            limits.check_contents(left)?;
+            limits.check_contents(right)?;
+            Ok(line_comparator(left, right))
+        },
+        on_pair,
+    )
+}
+
+/// The byte range of `line` (a subslice of `source`) in `source`
+pub(crate) fn span_in(source: &str, line: &str) -> Range<usize> {
+    let start = line.as_ptr() as usize - source.as_ptr() as usize;
+    start..start + line.len()
+}
+
+/// A pair of lines, as reported by each implementation's `try_day13_observed`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Pair {
+    /// 1-based, like the answer's sum
+    pub index: usize,
+    /// byte range of the left line in the input, without the '\n'
+    pub left: Range<usize>,
+    /// byte range of the right line in the input, without the '\n'
+    pub right: Range<usize>,
+    /// `Ordering::Less` if the pair is in the right order
+    pub result: Result<Ordering, Error>,
+}
+
+/// Why an implementation rejected its input
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// There are more than `max_pairs` pairs
+    TooManyPairs { max_pairs: usize },
+    /// A line is longer than `max_line_len` bytes
+    LineTooLong { max_line_len: usize },
+    /// A line nests lists more than `max_depth` levels deep
+    TooDeep { max_depth: usize },
+    /// A number has more than `max_number_len` digits
+    NumberTooLong { max_number_len: usize },
+    /// A line is not a packet. `offset` is the byte offset of the problem in the line.
+    Syntax { offset: usize, reason: &'static str },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::TooManyPairs { max_pairs } => write!(f, "more than {max_pairs} pairs"),
+            Error::LineTooLong { max_line_len } => {
+                write!(f, "a line is longer than {max_line_len} bytes")
+            }
+            Error::TooDeep { max_depth } => {
+                write!(f, "lists are nested more than {max_depth} levels deep")
+            }
+            Error::NumberTooLong { max_number_len } => {
+                write!(f, "a number has more than {max_number_len} digits")
+            }
+            Error::Syntax { offset, reason } => {
+                write!(f, "{reason} (at byte {offset} of the line)")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+/// Bounds on the input, for inputs that can't be trusted.
+///
+/// Every implementation has a `try_day13(input, &impl Bounds)`, which returns the matching [Error]
+/// when a bound is exceeded. Pass [Unbounded] to check nothing at no cost, or a [Limits].
+pub mod limits {
+    use super::Error;
+
+    /// The checks an implementation runs on its input. See [Limits] and [Unbounded].
+    pub trait Bounds {
+        /// `pairs` is the number of pairs seen so far, including the current one
+        fn check_pairs(&self, pairs: usize) -> Result<(), Error>;
+        fn check_line_len(&self, line: &str) -> Result<(), Error>;
+        /// `depth` is the number of lists open, including the one just opened
+        fn check_depth(&self, depth: usize) -> Result<(), Error>;
+        fn check_number_len(&self, len: usize) -> Result<(), Error>;
+        /// Scans a whole line for the depth and number length bounds,
+        /// for implementations that don't parse every byte themselves
+        fn check_contents(&self, line: &str) -> Result<(), Error>;
+    }
+
+    /// Each bound is inclusive, `usize::MAX` means unbounded.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct Limits {
+        /// most pairs in an input
+        pub max_pairs: usize,
+        /// longest line, in bytes (without the '\n')
+        pub max_line_len: usize,
+        /// most levels of nested lists in a line, `[[]]` is 2 deep
+        pub max_depth: usize,
+        /// most digits in a number
+        pub max_number_len: usize,
+    }
+
+    impl Limits {
+        pub const UNBOUNDED: Limits = Limits {
+            max_pairs: usize::MAX,
+            max_line_len: usize::MAX,
+            max_depth: usize::MAX,
+            max_number_len: usize::MAX,
+        };
+    }
+
+    impl Default for Limits {
+        fn default() -> Self {
+            Self::UNBOUNDED
+        }
+    }
+
+    impl Bounds for Limits {
+        #[inline(always)]
+        fn check_pairs(&self, pairs: usize) -> Result<(), Error> {
+            if pairs > self.max_pairs {
+                return Err(Error::TooManyPairs {
+                    max_pairs: self.max_pairs,
+                });
+            }
+            Ok(())
+        }
+
+        #[inline(always)]
+        fn check_line_len(&self, line: &str) -> Result<(), Error> {
+            if line.len() > self.max_line_len {
+                return Err(Error::LineTooLong {
+                    max_line_len: self.max_line_len,
+                });
+            }
+            Ok(())
+        }
+
+        #[inline(always)]
+        fn check_depth(&self, depth: usize) -> Result<(), Error> {
+            if depth > self.max_depth {
+                return Err(Error::TooDeep {
+                    max_depth: self.max_depth,
+                });
+            }
+            Ok(())
+        }
+
+        #[inline(always)]
+        fn check_number_len(&self, len: usize) -> Result<(), Error> {
+            if len > self.max_number_len {
+                return Err(Error::NumberTooLong {
+                    max_number_len: self.max_number_len,
+                });
+            }
+            Ok(())
+        }
+
+        fn check_contents(&self, line: &str) -> Result<(), Error> {
+            if self.max_depth == usize::MAX && self.max_number_len == usize::MAX {
+                return Ok(());
+            }
+            let (mut depth, mut number_len) = (0, 0);
+            for &byte in line.as_bytes() {
+                match byte {
+                    b'0'..=b'9' => {
+                        number_len += 1;
+                        self.check_number_len(number_len)?;
+                        continue;
+                    }
+                    b'[' => {
+                        depth += 1;
+                        self.check_depth(depth)?;
+                    }
+                    b']' => depth = usize::saturating_sub(depth, 1),
+                    _ => {}
+                }
+                number_len = 0;
+            }
+            Ok(())
+        }
+    }
+
+    /// A ZST that accepts everything. The checks are no-ops, so they compile away.
+    #[derive(Clone, Copy, Debug, Default)]
+    pub struct Unbounded;
+
+    impl Bounds for Unbounded {
+        #[inline(always)]
+        fn check_pairs(&self, _: usize) -> Result<(), Error> {
+            Ok(())
+        }
+
+        #[inline(always)]
+        fn check_line_len(&self, _: &str) -> Result<(), Error> {
+            Ok(())
+        }
+
+        #[inline(always)]
+        fn check_depth(&self, _: usize) -> Result<(), Error> {
+            Ok(())
+        }
+
+        #[inline(always)]
+        fn check_number_len(&self, _: usize) -> Result<(), Error> {
+            Ok(())
+        }
+
+        #[inline(always)]
+        fn check_contents(&self, _: &str) -> Result<(), Error> {
+            Ok(())
+        }
+    }
+}
+
 /// Object pool that moves the resources.
 ///
 /// See [Alloc] for the relevant trait.
 /// # Impls
 /// See [GlobalHeapProxy] for a 0-cost pool that simply `new`s and `drop`s the items.
-/// See [ResPool] for
-pub(crate) mod res_pool {
+/// See [ResPool] for a pool that keeps the items deposited into it, within a [Retention].
+/// See [GuardPool] for a pool that lends its items out behind guards instead of moving them.
+/// See [SyncPool] and [LocalPool] for pools that can be used from several threads (`std` only).
+/// See [Instrumented] to record the usage of an [Alloc].
+pub mod res_pool {
+    use alloc::{rc::Rc, string::String, vec::Vec};
+    use core::{
+        cell::RefCell,
+        fmt, mem,
+        ops::{Deref, DerefMut},
+    };
+    #[cfg(feature = "std")]
+    use std::{
+        boxed::Box,
+        sync::{
+            atomic::{self, AtomicUsize},
+            Mutex, MutexGuard, PoisonError,
+        },
+        thread::LocalKey,
+    };
+
     /// A trait for behaving as an allocator for a concrete type T.
     pub trait Alloc<T> {
         /// Transfers ownership of `item` from the caller to [self]
@@ -38,6 +327,69 @@ pub(crate) mod res_pool {
         ///
         /// the instance may be freshly constructed
         fn withdraw(&mut self) -> T;
+
+        /// [withdraw](Alloc::withdraw), also saying whether the item was a stored one (`true`)
+        /// or freshly constructed. Pools that store items override this.
+        fn withdraw_reused(&mut self) -> (T, bool) {
+            (self.withdraw(), false)
+        }
+
+        /// Number of stored items, which `withdraw` can hand out without constructing a new one
+        fn held(&self) -> usize {
+            0
+        }
+
+        /// Heap memory kept alive by the stored items, in bytes
+        fn retained_bytes(&self) -> usize {
+            0
+        }
+    }
+
+    /// Heap memory owned by a pooled resource
+    pub trait Footprint {
+        /// bytes of heap capacity, whether used or not
+        fn footprint(&self) -> usize;
+    }
+
+    impl<T> Footprint for Vec<T> {
+        fn footprint(&self) -> usize {
+            self.capacity() * mem::size_of::<T>()
+        }
+    }
+
+    impl Footprint for String {
+        fn footprint(&self) -> usize {
+            self.capacity()
+        }
+    }
+
+    /// The reset a pooled resource goes through before it is stored,
+    /// so every item a pool hands out starts out empty.
+    pub trait Recycle: Footprint {
+        /// Empties the item, keeping its capacity
+        fn recycle(&mut self);
+        /// Lowers the [Footprint] to about `bytes`, if it is larger
+        fn shrink_to_bytes(&mut self, bytes: usize);
+    }
+
+    impl<T> Recycle for Vec<T> {
+        fn recycle(&mut self) {
+            self.clear();
+        }
+
+        fn shrink_to_bytes(&mut self, bytes: usize) {
+            self.shrink_to(bytes / mem::size_of::<T>().max(1));
+        }
+    }
+
+    impl Recycle for String {
+        fn recycle(&mut self) {
+            self.clear();
+        }
+
+        fn shrink_to_bytes(&mut self, bytes: usize) {
+            self.shrink_to(bytes);
+        }
     }
 
     /// A ZST that constructs and drops the [Default] resource on-demand
@@ -59,27 +411,949 @@ pub(crate) mod res_pool {
     /// and to `deposit(T)` that instance when you are done with it.
     /// Because the values are `moved`, there is no requirement that the item be returned to the
     /// same ResPool - you could return to a different ResPool or just Drop the item.
+    ///
+    /// Deposited items are [Recycle]d, then kept within the pool's [Retention] limits.
     pub struct ResPool<'a, T> {
         items: Vec<T>,
         make_new: &'a mut dyn FnMut() -> T,
+        retention: Retention,
     }
 
     impl<'a, T> ResPool<'a, T> {
-        pub(crate) fn new(supplier: &'a mut dyn FnMut() -> T) -> Self {
+        pub fn new(supplier: &'a mut dyn FnMut() -> T) -> Self {
+            Self::with_retention(supplier, Retention::UNBOUNDED)
+        }
+
+        pub fn with_retention(supplier: &'a mut dyn FnMut() -> T, retention: Retention) -> Self {
             ResPool {
                 items: Vec::new(),
                 make_new: supplier,
+                retention,
             }
         }
+
+        /// Drops every stored item, and the stack that held them.
+        ///
+        /// ex: after an unusually large input, when the next ones are expected to be small again
+        pub fn trim(&mut self) {
+            self.items = Vec::new();
+        }
     }
 
-    impl<'a, T> Alloc<T> for ResPool<'a, T> {
+    impl<'a, T: Recycle> Alloc<T> for ResPool<'a, T> {
         fn deposit(&mut self, item: T) {
-            self.items.push(item);
+            if let Some(item) = self.retention.fit(item) {
+                if self.items.len() < self.retention.max_items {
+                    self.items.push(item);
+                }
+            }
         }
 
         fn withdraw(&mut self) -> T {
             self.items.pop().unwrap_or_else(&mut self.make_new)
         }
+
+        fn withdraw_reused(&mut self) -> (T, bool) {
+            match self.items.pop() {
+                Some(item) => (item, true),
+                None => ((self.make_new)(), false),
+            }
+        }
+
+        fn held(&self) -> usize {
+            self.items.len()
+        }
+
+        fn retained_bytes(&self) -> usize {
+            self.items.iter().map(Footprint::footprint).sum()
+        }
+    }
+
+    /// Limits on what a [ResPool] (or each shard of a `SyncPool`) keeps, so a single pathological
+    /// input can't pin large buffers for the life of the pool
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct Retention {
+        /// items deposited while this many are stored get dropped
+        pub max_items: usize,
+        /// largest [Footprint] an item may have when stored
+        pub max_item_bytes: usize,
+        /// what happens to items over `max_item_bytes`
+        pub oversized: Oversized,
+    }
+
+    impl Retention {
+        /// keeps every item, at whatever capacity it has
+        pub const UNBOUNDED: Retention = Retention {
+            max_items: usize::MAX,
+            max_item_bytes: usize::MAX,
+            oversized: Oversized::Drop,
+        };
+    }
+
+    impl Retention {
+        /// [Recycle]s `item`, and applies [Retention::oversized] if it is over the size limit.
+        /// `None` if it should be dropped instead of stored.
+        fn fit<T: Recycle>(&self, mut item: T) -> Option<T> {
+            item.recycle();
+            if item.footprint() > self.max_item_bytes {
+                return self.shrink_oversized(item);
+            }
+            Some(item)
+        }
+
+        /// Applies [Retention::oversized] to an item over the limit. Kept out of line, as it is
+        /// never reached by the (default) unbounded pools.
+        #[cold]
+        #[inline(never)]
+        fn shrink_oversized<T: Recycle>(&self, mut item: T) -> Option<T> {
+            match self.oversized {
+                Oversized::Drop => None,
+                Oversized::Shrink => {
+                    item.shrink_to_bytes(self.max_item_bytes);
+                    Some(item)
+                }
+            }
+        }
+    }
+
+    impl Default for Retention {
+        fn default() -> Self {
+            Self::UNBOUNDED
+        }
+    }
+
+    /// How a pool handles items over [Retention::max_item_bytes]
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum Oversized {
+        /// frees the item
+        Drop,
+        /// reallocates the item down to the limit
+        Shrink,
+    }
+
+    /// A pool that can be shared between threads: `&SyncPool` is the [Alloc].
+    ///
+    /// Items are kept in several stacks (shards), each behind its own lock. A thread uses its
+    /// own shard, and only tries the others (without waiting on them) when its own is empty.
+    /// Deposited items are [Recycle]d, then kept within the pool's [Retention] limits,
+    /// which apply to each shard.
+    #[cfg(feature = "std")]
+    pub struct SyncPool<T> {
+        shards: Box<[Mutex<Vec<T>>]>,
+        make_new: fn() -> T,
+        retention: Retention,
+    }
+
+    #[cfg(feature = "std")]
+    impl<T> SyncPool<T> {
+        /// One shard per core
+        pub fn new(make_new: fn() -> T) -> Self {
+            Self::with_retention(make_new, Retention::UNBOUNDED)
+        }
+
+        /// One shard per core, each kept within `retention`
+        pub fn with_retention(make_new: fn() -> T, retention: Retention) -> Self {
+            let cores = std::thread::available_parallelism().map_or(1, usize::from);
+            Self::with_shards(make_new, cores, retention)
+        }
+
+        pub fn with_shards(make_new: fn() -> T, shards: usize, retention: Retention) -> Self {
+            SyncPool {
+                shards: (0..shards.max(1)).map(|_| Mutex::default()).collect(),
+                make_new,
+                retention,
+            }
+        }
+
+        fn home_shard(&self) -> usize {
+            static NEXT_THREAD: AtomicUsize = AtomicUsize::new(0);
+            std::thread_local! {
+                static THREAD: usize = NEXT_THREAD.fetch_add(1, atomic::Ordering::Relaxed);
+            }
+            THREAD.with(|thread| *thread) % self.shards.len()
+        }
+    }
+
+    /// Locks a shard, ignoring poisoning: the stacks are never left half-updated
+    #[cfg(feature = "std")]
+    fn lock<T>(shard: &Mutex<Vec<T>>) -> MutexGuard<'_, Vec<T>> {
+        shard.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    #[cfg(feature = "std")]
+    impl<T: Recycle> Alloc<T> for &SyncPool<T> {
+        fn deposit(&mut self, item: T) {
+            // recycled (and shrunk) before taking the lock
+            if let Some(item) = self.retention.fit(item) {
+                let mut items = lock(&self.shards[self.home_shard()]);
+                if items.len() < self.retention.max_items {
+                    items.push(item);
+                }
+            }
+        }
+
+        fn withdraw(&mut self) -> T {
+            self.withdraw_reused().0
+        }
+
+        fn withdraw_reused(&mut self) -> (T, bool) {
+            let home = self.home_shard();
+            if let Some(item) = lock(&self.shards[home]).pop() {
+                return (item, true);
+            }
+            let others = self.shards.iter().cycle().skip(home + 1);
+            for shard in others.take(self.shards.len() - 1) {
+                if let Some(item) = shard.try_lock().ok().and_then(|mut items| items.pop()) {
+                    return (item, true);
+                }
+            }
+            ((self.make_new)(), false)
+        }
+
+        fn held(&self) -> usize {
+            self.shards.iter().map(|shard| lock(shard).len()).sum()
+        }
+
+        fn retained_bytes(&self) -> usize {
+            let items = |shard| lock(shard).iter().map(Footprint::footprint).sum::<usize>();
+            self.shards.iter().map(items).sum()
+        }
+    }
+
+    /// A pool whose items are kept in a thread local, so each thread reuses its own items
+    /// without any locking.
+    ///
+    /// Rust has no generic thread locals, so the storage is declared by the user of the pool:
+    /// ```ignore
+    /// std::thread_local! {
+    ///     static STRINGS: RefCell<Vec<String>> = RefCell::default();
+    /// }
+    /// let strings = &mut LocalPool::new(&STRINGS, String::new);
+    /// ```
+    /// Deposited items are [Recycle]d.
+    #[cfg(feature = "std")]
+    pub struct LocalPool<T: 'static> {
+        items: &'static LocalKey<RefCell<Vec<T>>>,
+        make_new: fn() -> T,
+    }
+
+    #[cfg(feature = "std")]
+    impl<T: 'static> LocalPool<T> {
+        pub fn new(items: &'static LocalKey<RefCell<Vec<T>>>, make_new: fn() -> T) -> Self {
+            LocalPool { items, make_new }
+        }
+    }
+
+    #[cfg(feature = "std")]
+    impl<T: Recycle + 'static> Alloc<T> for LocalPool<T> {
+        fn deposit(&mut self, mut item: T) {
+            item.recycle();
+            self.items.with_borrow_mut(|items| items.push(item));
+        }
+
+        fn withdraw(&mut self) -> T {
+            self.items
+                .with_borrow_mut(Vec::pop)
+                .unwrap_or_else(self.make_new)
+        }
+
+        fn withdraw_reused(&mut self) -> (T, bool) {
+            match self.items.with_borrow_mut(Vec::pop) {
+                Some(item) => (item, true),
+                None => ((self.make_new)(), false),
+            }
+        }
+
+        fn held(&self) -> usize {
+            self.items.with_borrow(Vec::len)
+        }
+
+        fn retained_bytes(&self) -> usize {
+            self.items
+                .with_borrow(|items| items.iter().map(Footprint::footprint).sum())
+        }
+    }
+
+    /// Wraps an [Alloc] and records [PoolStats] about how it is used.
+    ///
+    /// Opt-in: the unwrapped pools don't pay for the bookkeeping.
+    pub struct Instrumented<A> {
+        inner: A,
+        stats: PoolStats,
+    }
+
+    impl<A> Instrumented<A> {
+        pub fn new(inner: A) -> Self {
+            Instrumented {
+                inner,
+                stats: PoolStats::default(),
+            }
+        }
+
+        /// The counts so far, along with what the pool currently retains
+        pub fn stats<T>(&self) -> PoolStats
+        where
+            A: Alloc<T>,
+        {
+            PoolStats {
+                retained_bytes: self.inner.retained_bytes(),
+                ..self.stats
+            }
+        }
+    }
+
+    impl<T, A: Alloc<T>> Alloc<T> for Instrumented<A> {
+        fn deposit(&mut self, item: T) {
+            self.inner.deposit(item);
+            self.stats.high_water = self.stats.high_water.max(self.inner.held());
+        }
+
+        fn withdraw(&mut self) -> T {
+            self.withdraw_reused().0
+        }
+
+        /// Counts the withdrawal as a hit or miss by what the pool itself did, rather than by
+        /// what it held beforehand, which another thread may change in between
+        fn withdraw_reused(&mut self) -> (T, bool) {
+            let (item, reused) = self.inner.withdraw_reused();
+            if reused {
+                self.stats.hits += 1;
+            } else {
+                self.stats.misses += 1;
+            }
+            (item, reused)
+        }
+
+        fn held(&self) -> usize {
+            self.inner.held()
+        }
+
+        fn retained_bytes(&self) -> usize {
+            self.inner.retained_bytes()
+        }
+    }
+
+    /// Usage of a pool, as recorded by [Instrumented]
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub struct PoolStats {
+        /// withdrawals served by a stored item
+        pub hits: usize,
+        /// withdrawals that had to construct a new item
+        pub misses: usize,
+        /// most items stored at once
+        pub high_water: usize,
+        /// heap capacity of the stored items, in bytes
+        pub retained_bytes: usize,
+    }
+
+    impl fmt::Display for PoolStats {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(
+                f,
+                "{} hits, {} misses, high-water mark of {} items, {} bytes retained",
+                self.hits, self.misses, self.high_water, self.retained_bytes
+            )
+        }
+    }
+
+    /// A stack of T that lends out its items behind [Pooled] guards.
+    ///
+    /// Unlike [ResPool], items don't have to be handed back: dropping the guard [Recycle]s the item
+    /// and puts it back on the stack, so a panic or early return can't leak pooled items.
+    /// Cloning the handle is cheap, and every clone refers to the same stack.
+    pub struct GuardPool<T> {
+        shelf: Rc<Shelf<T>>,
+    }
+
+    struct Shelf<T> {
+        items: RefCell<Vec<T>>,
+        make_new: fn() -> T,
+    }
+
+    impl<T: Recycle + Default> GuardPool<T> {
+        pub fn new(make_new: fn() -> T) -> Self {
+            GuardPool {
+                shelf: Rc::new(Shelf {
+                    items: RefCell::new(Vec::new()),
+                    make_new,
+                }),
+            }
+        }
+
+        /// Lends out an item, which may be freshly constructed
+        pub fn take(&self) -> Pooled<T> {
+            let item = self
+                .shelf
+                .items
+                .borrow_mut()
+                .pop()
+                .unwrap_or_else(self.shelf.make_new);
+            Pooled {
+                item,
+                shelf: Rc::clone(&self.shelf),
+            }
+        }
+    }
+
+    impl<T> Clone for GuardPool<T> {
+        fn clone(&self) -> Self {
+            GuardPool {
+                shelf: Rc::clone(&self.shelf),
+            }
+        }
+    }
+
+    /// An item lent out by a [GuardPool], which goes back to the pool when this is dropped
+    pub struct Pooled<T: Recycle + Default> {
+        item: T,
+        shelf: Rc<Shelf<T>>,
+    }
+
+    impl<T: Recycle + Default> Deref for Pooled<T> {
+        type Target = T;
+
+        fn deref(&self) -> &T {
+            &self.item
+        }
+    }
+
+    impl<T: Recycle + Default> DerefMut for Pooled<T> {
+        fn deref_mut(&mut self) -> &mut T {
+            &mut self.item
+        }
+    }
+
+    impl<T: Recycle + Default> Drop for Pooled<T> {
+        fn drop(&mut self) {
+            // `T::default()` is a `Vec::new()`/`String::new()` here, which doesn't allocate
+            let mut item = mem::take(&mut self.item);
+            // recycling first: it may drop nested guards, which need to borrow their shelves too
+            item.recycle();
+            self.shelf.items.borrow_mut().push(item);
+        }
+    }
+}
+
+/// Bump allocator that frees everything at once.
+///
+/// Items are only ever appended, and [Arena::reset] drops all of them while keeping the capacity.
+/// Allocations are addressed by [Span] instead of by reference, so the arena can keep growing
+/// while earlier allocations are still in use.
+pub(crate) mod arena {
+    use super::{
+        limits::Bounds,
+        tree::{self, Builder},
+        Error,
+    };
+    use alloc::vec::Vec;
+    use core::{cmp::Ordering, ops::Range};
+
+    /// Location of a run of items in an [Arena]
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct Span {
+        pub start: usize,
+        pub end: usize,
+    }
+
+    impl Span {
+        pub fn len(&self) -> usize {
+            self.end - self.start
+        }
+    }
+
+    pub struct Arena<T> {
+        items: Vec<T>,
+    }
+
+    impl<T> Arena<T> {
+        pub fn new() -> Self {
+            Arena { items: Vec::new() }
+        }
+
+        /// Moves `items` into the arena, next to each other
+        pub fn alloc_from(&mut self, items: impl IntoIterator<Item = T>) -> Span {
+            let start = self.items.len();
+            self.items.extend(items);
+            Span {
+                start,
+                end: self.items.len(),
+            }
+        }
+
+        pub fn get(&self, span: Span) -> &[T] {
+            &self.items[span.start..span.end]
+        }
+
+        /// Frees every allocation. Any [Span] handed out before this is invalidated.
+        pub fn reset(&mut self) {
+            self.items.clear();
+        }
+    }
+
+    impl<T> Default for Arena<T> {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    /// A packet's element in [Trees]
+    #[derive(Clone, Copy)]
+    pub enum Node {
+        /// digits, wherever the [Digits] keep them
+        Num(Span),
+        /// items in the node arena
+        List(Span),
+    }
+
+    /// Where [Trees] keep the numbers' digits
+    pub trait Digits: Default {
+        /// Keeps the digits `line[digits]`
+        fn alloc(&mut self, line: &str, digits: Range<usize>) -> Span;
+        /// Compares a number of the left line with one of the right line
+        fn cmp(&self, left: (&str, Span), right: (&str, Span)) -> Ordering;
+        fn reset(&mut self);
+    }
+
+    /// Copies the digits
+    impl Digits for Arena<u8> {
+        fn alloc(&mut self, line: &str, digits: Range<usize>) -> Span {
+            self.alloc_from(line[digits].bytes())
+        }
+
+        fn cmp(&self, (_, left): (&str, Span), (_, right): (&str, Span)) -> Ordering {
+            self.get(left).cmp(self.get(right))
+        }
+
+        fn reset(&mut self) {
+            Arena::reset(self);
+        }
+    }
+
+    /// Leaves the digits in the line, so the nodes locate them there instead of borrowing the line
+    #[derive(Default)]
+    pub struct InLine;
+
+    impl Digits for InLine {
+        fn alloc(&mut self, _: &str, digits: Range<usize>) -> Span {
+            Span {
+                start: digits.start,
+                end: digits.end,
+            }
+        }
+
+        fn cmp(&self, (left_line, l): (&str, Span), (right_line, r): (&str, Span)) -> Ordering {
+            left_line[l.start..l.end].cmp(&right_line[r.start..r.end])
+        }
+
+        fn reset(&mut self) {}
+    }
+
+    /// Arenas for the trees of one pair of lines, which are reset after the comparison instead of
+    /// the trees being torn down element by element. Kept between pairs, with their capacity.
+    #[derive(Default)]
+    pub struct Trees<D> {
+        nodes: Nodes<D>,
+        /// the first child (in [Nodes::pending]) of each list that is still being parsed,
+        /// innermost last
+        open: Vec<usize>,
+        /// the remaining items of the lists being compared, innermost last
+        pairs: Vec<(Span, Span)>,
+    }
+
+    #[derive(Default)]
+    struct Nodes<D> {
+        nodes: Arena<Node>,
+        digits: D,
+        /// children of the lists that are still being parsed, innermost list last.
+        /// A list's children are moved into [Nodes::nodes] once its closing ']' is reached.
+        pending: Vec<Node>,
+    }
+
+    impl<D: Digits> Builder for Nodes<D> {
+        type Node = Node;
+        type Open = usize;
+
+        fn number(&mut self, line: &str, digits: Range<usize>) -> Node {
+            Node::Num(self.digits.alloc(line, digits))
+        }
+
+        fn open(&mut self) -> usize {
+            self.pending.len()
+        }
+
+        fn push(&mut self, _: &mut usize, item: Node) {
+            self.pending.push(item);
+        }
+
+        fn close(&mut self, first_child: usize) -> Node {
+            Node::List(self.nodes.alloc_from(self.pending.drain(first_child..)))
+        }
+
+        fn abandon(&mut self, open: &mut Vec<usize>) {
+            open.clear();
+            self.pending.clear();
+        }
+    }
+
+    impl<D: Digits> Trees<D> {
+        /// Parses and compares a pair of lines.
+        /// Fails on invalid lines, and on input beyond the depth and number length `limits`.
+        pub fn compare(
+            &mut self,
+            left: &str,
+            right: &str,
+            limits: &impl Bounds,
+        ) -> Result<Ordering, Error> {
+            let cmp = self.parse_and_cmp(left, right, limits);
+            self.nodes.nodes.reset();
+            self.nodes.digits.reset();
+            cmp
+        }
+
+        fn parse_and_cmp(
+            &mut self,
+            left: &str,
+            right: &str,
+            limits: &impl Bounds,
+        ) -> Result<Ordering, Error> {
+            let left_root = tree::parse(left, limits, &mut self.nodes, &mut self.open)?;
+            let right_root = tree::parse(right, limits, &mut self.nodes, &mut self.open)?;
+            let Nodes { nodes, digits, .. } = &self.nodes;
+            let cmp_nums = |l, r| digits.cmp((left, l), (right, r));
+            let pending = &mut self.pairs;
+            let (mut l, mut r) = (left_root, right_root);
+            let cmp = 'cmp: loop {
+                let cmp = match (l, r) {
+                    (Node::Num(l), Node::Num(r)) => cmp_nums(l, r),
+                    (Node::Num(l), Node::List(r)) => {
+                        cmp_num_with_list(nodes, r, |r| cmp_nums(l, r))
+                    }
+                    (Node::List(l), Node::Num(r)) => {
+                        cmp_num_with_list(nodes, l, |l| cmp_nums(l, r).reverse()).reverse()
+                    }
+                    (Node::List(l), Node::List(r)) => {
+                        pending.push((l, r));
+                        Ordering::Equal
+                    }
+                };
+                if cmp.is_ne() {
+                    break cmp;
+                }
+                // on to the next pair of items
+                (l, r) = loop {
+                    let Some((l_items, r_items)) = pending.last_mut() else {
+                        break 'cmp Ordering::Equal;
+                    };
+                    match (nodes.get(*l_items).first(), nodes.get(*r_items).first()) {
+                        (None, None) => {
+                            pending.pop();
+                        }
+                        (None, Some(_)) => break 'cmp Ordering::Less,
+                        (Some(_), None) => break 'cmp Ordering::Greater,
+                        (Some(&l), Some(&r)) => {
+                            l_items.start += 1;
+                            r_items.start += 1;
+                            break (l, r);
+                        }
+                    }
+                };
+            };
+            pending.clear();
+            Ok(cmp)
+        }
+    }
+
+    /// Compares a number with the list at `list` as if it were a list of just that number,
+    /// like [tree]'s comparison does
+    fn cmp_num_with_list(
+        nodes: &Arena<Node>,
+        mut list: Span,
+        cmp_num: impl Fn(Span) -> Ordering,
+    ) -> Ordering {
+        let mut more_items = false;
+        loop {
+            let Some(&first) = nodes.get(list).first() else {
+                return Ordering::Greater;
+            };
+            more_items |= list.len() > 1;
+            match first {
+                Node::Num(num) => {
+                    return match cmp_num(num) {
+                        Ordering::Equal if more_items => Ordering::Less,
+                        cmp => cmp,
+                    }
+                }
+                Node::List(items) => list = items,
+            }
+        }
+    }
+}
+
+/// Iterative parsing of packets, and comparison of packets held as trees of `Vec`s.
+///
+/// A recursive parser or comparison needs a stack frame per level of nesting, and a line with a few
+/// hundred thousand `[` overflows the stack. [parse](tree::parse) keeps the lists that are still
+/// open on an explicit stack instead, and leaves what they are built from to a [tree::Builder].
+///
+/// [compare](tree::compare) keeps the lists it is in the middle of on a stack of [tree::Level]s.
+/// The lists are moved onto that stack (their parents keep an empty `Vec` in their place) and put
+/// back before it returns. So the stack doesn't borrow the trees, and it can be kept between pairs,
+/// with its capacity.
+pub(crate) mod tree {
+    use super::{limits::Bounds, Error};
+    use alloc::vec::Vec;
+    use core::{cmp::Ordering, mem, ops::Range};
+
+    /// How [parse] builds a tree, e.g. from pooled Vecs, or in an arena
+    pub trait Builder {
+        /// A parsed element
+        type Node;
+        /// A list whose closing ']' hasn't been reached yet
+        type Open;
+
+        /// The number whose digits are `line[digits]`
+        fn number(&mut self, line: &str, digits: Range<usize>) -> Self::Node;
+        fn open(&mut self) -> Self::Open;
+        fn push(&mut self, list: &mut Self::Open, item: Self::Node);
+        fn close(&mut self, list: Self::Open) -> Self::Node;
+        /// Frees the lists (and their contents) left on `open` by an invalid line
+        fn abandon(&mut self, open: &mut Vec<Self::Open>);
+    }
+
+    /// Parses a line without recursing: the lists that are still open wait on `open`,
+    /// which is empty before and after.
+    /// Fails on invalid lines, and on input beyond the depth and number length `limits`.
+    pub fn parse<B: Builder>(
+        line: &str,
+        limits: &impl Bounds,
+        builder: &mut B,
+        open: &mut Vec<B::Open>,
+    ) -> Result<B::Node, Error> {
+        let start = line.len() - line.trim_start().len();
+        let end = start + line.trim().len();
+        if line[start..end].bytes().all(|byte| byte.is_ascii_digit()) {
+            limits.check_number_len(end - start)?;
+            return Ok(builder.number(line, start..end));
+        }
+
+        let parsed = parse_list(line, start..end, limits, builder, open);
+        if parsed.is_err() {
+            // hand back the partially built lists
+            builder.abandon(open);
+        }
+        parsed
+    }
+
+    /// Parses `line[packet]`, which doesn't start or end with whitespace
+    fn parse_list<B: Builder>(
+        line: &str,
+        packet: Range<usize>,
+        limits: &impl Bounds,
+        builder: &mut B,
+        open: &mut Vec<B::Open>,
+    ) -> Result<B::Node, Error> {
+        let syntax = |offset, reason| Err(Error::Syntax { offset, reason });
+        let bytes = &line.as_bytes()[..packet.end];
+        match bytes[packet.start] {
+            b'[' => {}
+            b'0'..=b'9' => {
+                let end = (packet.start..packet.end)
+                    .find(|&idx| !bytes[idx].is_ascii_digit())
+                    .unwrap();
+                return match bytes[end] {
+                    b',' => syntax(
+                        end,
+                        "unexpected comma (top-level needs to be a list, with '[' and ']')",
+                    ),
+                    b']' => syntax(end, "unexpected ']' (did you forget the opening '['?"),
+                    _ => syntax(end, "unexpected character after complete number"),
+                };
+            }
+            _ => return syntax(packet.start, "invalid character"),
+        }
+
+        // after a '[' or ',', as opposed to after an element
+        let mut expect_element = true;
+        let mut idx = packet.start;
+        loop {
+            let Some(&byte) = bytes.get(idx) else {
+                return syntax(idx, "expected a closing brace, but reached end of input");
+            };
+            match byte {
+                b' ' => {}
+                b'[' if expect_element => {
+                    limits.check_depth(open.len() + 1)?;
+                    let list = builder.open();
+                    open.push(list);
+                }
+                b'0'..=b'9' if expect_element => {
+                    let start = idx;
+                    while bytes.get(idx + 1).is_some_and(u8::is_ascii_digit) {
+                        idx += 1;
+                    }
+                    limits.check_number_len(idx + 1 - start)?;
+                    let num = builder.number(line, start..idx + 1);
+                    builder.push(open.last_mut().unwrap(), num);
+                    expect_element = false;
+                }
+                b']' if open.len() == 1 && idx + 1 < bytes.len() => {
+                    return syntax(
+                        idx + 1,
+                        "unexpected character after the closing ']' of the line",
+                    );
+                }
+                b']' => {
+                    let list = builder.close(open.pop().unwrap());
+                    let Some(parent) = open.last_mut() else {
+                        return Ok(list);
+                    };
+                    builder.push(parent, list);
+                    expect_element = false;
+                }
+                b',' if expect_element => return syntax(idx, "expected element before comma"),
+                b',' => expect_element = true,
+                _ if expect_element => return syntax(idx, "invalid character"),
+                _ => return syntax(idx, "expected a closing brace or comma after element"),
+            }
+            idx += 1;
+        }
+    }
+
+    /// A packet's element, as far as [compare] is concerned
+    pub trait Element: Sized {
+        /// What a number is kept as, e.g. its digits
+        type Num: ?Sized;
+
+        fn view(&self) -> View<'_, Self>;
+        /// The items, `None` for a number
+        fn items_mut(&mut self) -> Option<&mut Vec<Self>>;
+    }
+
+    pub enum View<'a, E: Element> {
+        Num(&'a E::Num),
+        List(&'a [E]),
+    }
+
+    /// Two lists being compared, moved out of their parents, and the index of their next items
+    pub struct Level<E> {
+        left: Vec<E>,
+        right: Vec<E>,
+        next: usize,
+    }
+
+    impl<E: Element> Level<E> {
+        fn take(left: &mut E, right: &mut E) -> Self {
+            Level {
+                left: mem::take(left.items_mut().unwrap()),
+                right: mem::take(right.items_mut().unwrap()),
+                next: 0,
+            }
+        }
+
+        fn put_back(self, left: &mut E, right: &mut E) {
+            *left.items_mut().unwrap() = self.left;
+            *right.items_mut().unwrap() = self.right;
+        }
+    }
+
+    /// Compares two packets without recursing. `pending` is empty before and after.
+    /// `cmp_nums` compares a number of `left` with one of `right`.
+    pub fn compare<E: Element>(
+        left: &mut E,
+        right: &mut E,
+        pending: &mut Vec<Level<E>>,
+        cmp_nums: impl Fn(&E::Num, &E::Num) -> Ordering,
+    ) -> Ordering {
+        if let Some(cmp) = cmp_unless_lists(left, right, &cmp_nums) {
+            return cmp;
+        }
+        pending.push(Level::take(left, right));
+        let cmp = cmp_levels(left, right, pending, &cmp_nums);
+        // put the lists that are still on the stack back where they came from
+        while !pending.is_empty() {
+            pop_level(left, right, pending);
+        }
+        cmp
+    }
+
+    fn cmp_levels<E: Element>(
+        left: &mut E,
+        right: &mut E,
+        pending: &mut Vec<Level<E>>,
+        cmp_nums: &impl Fn(&E::Num, &E::Num) -> Ordering,
+    ) -> Ordering {
+        while let Some(level) = pending.last_mut() {
+            let idx = level.next;
+            level.next += 1;
+            match (level.left.get_mut(idx), level.right.get_mut(idx)) {
+                (None, None) => pop_level(left, right, pending),
+                (None, Some(_)) => return Ordering::Less,
+                (Some(_), None) => return Ordering::Greater,
+                (Some(l), Some(r)) => match cmp_unless_lists(l, r, cmp_nums) {
+                    Some(Ordering::Equal) => {}
+                    Some(cmp) => return cmp,
+                    None => {
+                        let inner = Level::take(l, r);
+                        pending.push(inner);
+                    }
+                },
+            }
+        }
+        Ordering::Equal
+    }
+
+    /// Puts the innermost level's lists back into their parents, or into the roots
+    fn pop_level<E: Element>(left: &mut E, right: &mut E, pending: &mut Vec<Level<E>>) {
+        let level = pending.pop().unwrap();
+        match pending.last_mut() {
+            Some(parent) => {
+                let idx = parent.next - 1;
+                level.put_back(&mut parent.left[idx], &mut parent.right[idx]);
+            }
+            None => level.put_back(left, right),
+        }
+    }
+
+    /// Compares two elements, unless both are lists
+    fn cmp_unless_lists<E: Element>(
+        left: &E,
+        right: &E,
+        cmp_nums: &impl Fn(&E::Num, &E::Num) -> Ordering,
+    ) -> Option<Ordering> {
+        Some(match (left.view(), right.view()) {
+            (View::Num(l), View::Num(r)) => cmp_nums(l, r),
+            (View::Num(l), View::List(r)) => cmp_num_with_list(r, |r| cmp_nums(l, r)),
+            (View::List(l), View::Num(r)) => {
+                cmp_num_with_list(l, |l| cmp_nums(l, r).reverse()).reverse()
+            }
+            (View::List(_), View::List(_)) => return None,
+        })
+    }
+
+    /// Compares a number with `list` as if it were a list of just that number.
+    /// `cmp_num` compares the number with a number in `list`.
+    ///
+    /// That only ever looks at the first item of each nested list, so it needs no stack:
+    /// the number is less if the first number is equal, but some list on the way has more items.
+    fn cmp_num_with_list<E: Element>(
+        mut list: &[E],
+        cmp_num: impl Fn(&E::Num) -> Ordering,
+    ) -> Ordering {
+        let mut more_items = false;
+        loop {
+            let Some(first) = list.first() else {
+                return Ordering::Greater;
+            };
+            more_items |= list.len() > 1;
+            match first.view() {
+                View::Num(num) => {
+                    return match cmp_num(num) {
+                        Ordering::Equal if more_items => Ordering::Less,
+                        cmp => cmp,
+                    }
+                }
+                View::List(items) => list = items,
+            }
+        }
     }
 }
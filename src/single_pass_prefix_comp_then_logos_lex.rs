@@ -1,13 +1,16 @@
 //! like [crate::prefix_comp_then_logos_lex], but lazily finds the right line.
 
+use crate::shared::{limits::Bounds, span_in, Error, Pair};
+use core::{cmp::Ordering, iter};
 use logos::{Lexer, Logos};
-use std::{cmp::Ordering, iter};
 
 pub fn day13(mut input: &str) -> usize {
     let mut count = 0;
     let mut idx = 1;
     while !input.is_empty() {
-        let Some((left, rem)) = input.split_once('\n') else { break; };
+        let Some((left, rem)) = input.split_once('\n') else {
+            break;
+        };
 
         let (cmp, rem_idx_after_comparison) = compare_first_line(left, rem);
         debug_assert!({
@@ -28,6 +31,53 @@ pub fn day13(mut input: &str) -> usize {
     }
     count
 }
+/// [day13], but returns an error on input beyond `limits`, which are checked before comparing.
+/// As in [crate::prefix_comp_then_logos_lex], bounding the depth or number length
+/// adds a full scan of both lines before they are compared.
+/// The lines are not validated: invalid packets still panic, or give a meaningless answer.
+pub fn try_day13(input: &str, limits: &impl Bounds) -> Result<usize, Error> {
+    try_day13_observed(input, limits, |_| {})
+}
+
+/// [try_day13], also reporting each pair to `on_pair`
+pub fn try_day13_observed(
+    source: &str,
+    limits: &impl Bounds,
+    mut on_pair: impl FnMut(Pair),
+) -> Result<usize, Error> {
+    let mut input = source;
+    let mut count = 0;
+    let mut idx = 1;
+    while !input.is_empty() {
+        let Some((left, rem)) = input.split_once('\n') else {
+            break;
+        };
+        // finding the right line up front gives up on the lazy search [day13] does,
+        // but the comparison must not scan a line that is beyond the limits
+        let (right, next) = rem.split_once('\n').unwrap_or((rem, ""));
+        let result = limits
+            .check_pairs(idx)
+            .and_then(|()| limits.check_line_len(left))
+            .and_then(|()| limits.check_contents(left))
+            .and_then(|()| limits.check_line_len(right))
+            .and_then(|()| limits.check_contents(right))
+            .map(|()| compare_first_line(left, rem).0);
+        on_pair(Pair {
+            index: idx,
+            left: span_in(source, left),
+            right: span_in(source, right),
+            result,
+        });
+        if result?.is_lt() {
+            count += idx;
+        }
+
+        input = next.trim_start_matches('\n');
+        idx += 1;
+    }
+    Ok(count)
+}
+
 /// Compares left against the first line in rem.
 /// returns (left.cmp(rem.first_line), num_bytes_consumed_from_rem)
 fn compare_first_line(left: &str, rem: &str) -> (Ordering, usize) {
@@ -126,8 +176,14 @@ fn compare_first_line(left: &str, rem: &str) -> (Ordering, usize) {
                 });
             let deeper_number = match deeper_first_comparable_token {
                 Token::Comma | Token::Newline | Token::LBrace => unreachable!(),
+                // the list's first item is an empty list, which is less than the number
                 Token::RBrace => {
-                    return (Ordering::Greater, index_into_rem + deeper_lexer.span().end)
+                    return match which_is_list {
+                        WhichIsList::Left => (Ordering::Less, index_into_rem),
+                        WhichIsList::Right => {
+                            (Ordering::Greater, index_into_rem + deeper_lexer.span().end)
+                        }
+                    }
                 }
                 Token::Number => deeper_lexer.slice(),
             };
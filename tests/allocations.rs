@@ -0,0 +1,99 @@
+//! Checks the memory claims in the README with a counting global allocator,
+//! instead of heaptrack screenshots.
+
+#[path = "support/counting_alloc.rs"]
+mod counting_alloc;
+
+use counting_alloc::{measure, CountingAlloc};
+use day13_compare::*;
+use duplicate::duplicate;
+
+#[global_allocator]
+static ALLOC: CountingAlloc = CountingAlloc;
+
+fn repeated(base: &str, times: usize) -> String {
+    let mut base = base.to_string();
+    base.push_str("\n\n");
+    base = base.repeat(times);
+    base.truncate(base.len() - 2);
+    base
+}
+
+fn inputs() -> Vec<String> {
+    let ten_kb_number = "1029637485".repeat(1000);
+    vec![
+        SAMPLE.to_string(),
+        repeated(SAMPLE, 1000),
+        format!("[{ten_kb_number}]\n[{ten_kb_number}]"),
+        include_str!("../benches/resources/alternating_deep_nesting").to_string(),
+        include_str!("../benches/resources/left_longer").to_string(),
+        include_str!("../benches/resources/long_mixed_lines").to_string(),
+        include_str!("../benches/resources/right_longer").to_string(),
+    ]
+}
+
+duplicate! {
+    [
+        func name;
+        [input_handling_baseline::day13] [input_handling_baseline_never_allocates];
+        [manual_lex::day13] [manual_lex_never_allocates];
+        [logos_lex::day13] [logos_lex_never_allocates];
+        [prefix_comp_then_logos_lex::day13::<16>] [prefix_comp_then_logos_lex_16_never_allocates];
+        [prefix_comp_then_logos_lex::day13::<128>] [prefix_comp_then_logos_lex_128_never_allocates];
+        [single_pass_prefix_comp_then_logos_lex::day13] [single_pass_prefix_comp_never_allocates];
+    ]
+    #[test]
+    fn name() {
+        for input in inputs() {
+            let (_, stats) = measure(|| func(&input));
+            assert_eq!(stats.allocations, 0, "{stats:?}");
+        }
+    }
+}
+
+#[test]
+fn naive_pooled_stops_allocating_once_warmed_up() {
+    let once = SAMPLE;
+    let ten_times = repeated(SAMPLE, 10);
+    let thousand = repeated(SAMPLE, 1000);
+
+    // a fresh pool per call: the first repeats fill it (and grow its items to size),
+    // the rest only reuse
+    let (_, warm_up) = measure(|| naive::pooled::day13(&ten_times));
+    let (_, all) = measure(|| naive::pooled::day13(&thousand));
+    assert!(warm_up.allocations > 0);
+    assert_eq!(all, warm_up);
+
+    // a pool that outlives the call is already warm the second time
+    #[cfg(feature = "std")]
+    {
+        naive::pooled::day13_thread_local(&thousand);
+        let (_, warm) = measure(|| naive::pooled::day13_thread_local(&thousand));
+        assert_eq!(warm.allocations, 0, "{warm:?}");
+    }
+
+    // for contrast, every repeat allocates as much as the first
+    let (_, no_pool_once) = measure(|| naive::no_pool::day13(once));
+    let twice = repeated(SAMPLE, 2);
+    let (_, no_pool_twice) = measure(|| naive::no_pool::day13(&twice));
+    let (_, no_pool_all) = measure(|| naive::no_pool::day13(&thousand));
+    let per_repeat = no_pool_twice.allocations - no_pool_once.allocations;
+    assert!(per_repeat > 0);
+    assert_eq!(
+        no_pool_all.allocations,
+        no_pool_once.allocations + 999 * per_repeat
+    );
+}
+
+#[test]
+fn naive_guarded_stops_allocating_once_warmed_up() {
+    let ten_times = repeated(SAMPLE, 10);
+    let thousand = repeated(SAMPLE, 1000);
+
+    // like `naive::pooled`: the guards return every item to its pool, so only the first repeats
+    // (and the pools' bookkeeping) allocate
+    let (_, warm_up) = measure(|| naive::guarded::day13(&ten_times));
+    let (_, all) = measure(|| naive::guarded::day13(&thousand));
+    assert!(warm_up.allocations > 0);
+    assert_eq!(all, warm_up);
+}
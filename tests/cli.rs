@@ -0,0 +1,163 @@
+//! Runs the `day13` and `gen` binaries end-to-end, on the files in `benches/resources` and on generated inputs
+
+#[path = "support/resources.rs"]
+mod resources;
+
+use day13_compare::registry::IMPLEMENTATIONS;
+use resources::resources;
+use std::{
+    io::Write,
+    path::PathBuf,
+    process::{Command, Output, Stdio},
+};
+
+fn day13(args: &[&str], stdin: Option<&str>) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_day13"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut child_stdin = child.stdin.take().unwrap();
+    child_stdin
+        .write_all(stdin.unwrap_or_default().as_bytes())
+        .unwrap();
+    drop(child_stdin);
+    child.wait_with_output().unwrap()
+}
+
+fn temp_file(name: &str, contents: &[u8]) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("day13-cli-{}-{name}", std::process::id()));
+    std::fs::write(&path, contents).unwrap();
+    path
+}
+
+#[test]
+fn every_implementation_answers_every_resource() {
+    for resource in resources().unwrap() {
+        let path = resource.path.to_str().unwrap();
+        if resource.meta.invalid() {
+            assert_eq!(day13(&[path], None).status.code(), Some(65), "{path}");
+            continue;
+        }
+        let input = std::fs::read_to_string(path).unwrap();
+        let expected = format!("{}\n", day13_compare::naive::pooled::day13(&input));
+
+        let default = day13(&[path], None);
+        assert!(default.status.success(), "{path}: {default:?}");
+        assert_eq!(String::from_utf8_lossy(&default.stdout), expected);
+
+        for implementation in IMPLEMENTATIONS {
+            let output = day13(&["--impl", implementation.name, path], None);
+            assert!(output.status.success(), "{path}: {output:?}");
+            assert_eq!(
+                String::from_utf8_lossy(&output.stdout),
+                expected,
+                "{} on {path}",
+                implementation.name
+            );
+        }
+    }
+}
+
+#[test]
+fn reads_stdin() {
+    let output = day13(&[], Some(day13_compare::SAMPLE));
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(output.stdout, b"13\n");
+
+    let output = day13(&["--impl", "logos_lex", "-"], Some(day13_compare::SAMPLE));
+    assert_eq!(output.stdout, b"13\n");
+}
+
+#[test]
+fn prints_pool_stats() {
+    let output = day13(
+        &["--impl", "naive::pooled", "--stats"],
+        Some(day13_compare::SAMPLE),
+    );
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(output.stdout, b"13\n");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let pools: Vec<_> = stderr.lines().map(|line| line.split(": ").next()).collect();
+    assert_eq!(
+        pools,
+        [Some("naive::pooled lists"), Some("naive::pooled strings")]
+    );
+
+    let code = |args: &[&str]| day13(args, Some(day13_compare::SAMPLE)).status.code();
+    assert_eq!(
+        code(&["--impl", "naive_slice::no_pool", "--stats"]),
+        Some(0)
+    );
+    // no stdin, as a usage error exits before reading it
+    let usage_error = day13(&["--impl", "logos_lex", "--stats"], None);
+    assert_eq!(usage_error.status.code(), Some(64));
+}
+
+#[test]
+fn exit_codes() {
+    let invalid = temp_file("invalid", b"[1,,2]\n[1]");
+    let invalid_chars = temp_file("invalid-chars", b"[a]\n[b]");
+    let not_utf8 = temp_file("not-utf8", b"[1]\n[\xff]");
+    let missing = std::env::temp_dir().join("day13-cli-this-file-does-not-exist");
+    let code = |args: &[&str]| day13(args, None).status.code();
+
+    assert_eq!(code(&[invalid.to_str().unwrap()]), Some(65));
+    // panics on this input instead of returning an error
+    assert_eq!(
+        code(&["--impl", "manual_lex", invalid_chars.to_str().unwrap()]),
+        Some(65)
+    );
+    assert_eq!(code(&[not_utf8.to_str().unwrap()]), Some(65));
+    assert_eq!(code(&[missing.to_str().unwrap()]), Some(74));
+    assert_eq!(code(&["--impl", "no_such_impl"]), Some(64));
+    assert_eq!(code(&["--no-such-flag"]), Some(64));
+    assert_eq!(code(&["--list"]), Some(0));
+
+    std::fs::remove_file(invalid).unwrap();
+    std::fs::remove_file(invalid_chars).unwrap();
+    std::fs::remove_file(not_utf8).unwrap();
+}
+
+#[test]
+fn gen_writes_inputs_day13_can_read() {
+    let gen = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_gen"))
+            .args(args)
+            .output()
+            .unwrap()
+    };
+
+    let sample = gen(&["--preset", "sample"]);
+    assert!(sample.status.success(), "{sample:?}");
+    assert_eq!(sample.stdout, day13_compare::SAMPLE.as_bytes());
+
+    let args = [
+        "--seed",
+        "3",
+        "--pairs",
+        "40",
+        "--depth",
+        "1-8",
+        "--equal-percent",
+        "20",
+    ];
+    let generated = gen(&args);
+    assert!(generated.status.success(), "{generated:?}");
+    assert_eq!(generated.stdout, gen(&args).stdout);
+    let input = String::from_utf8(generated.stdout).unwrap();
+    let output = day13(&[], Some(&input));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        format!("{}\n", day13_compare::naive::pooled::day13(&input))
+    );
+
+    let code = |args: &[&str]| gen(args).status.code();
+    assert_eq!(code(&["--list"]), Some(0));
+    assert_eq!(code(&["--preset", "no-such-preset"]), Some(64));
+    assert_eq!(code(&["--preset", "sample", "--seed", "1"]), Some(64));
+    assert_eq!(code(&["--depth", "0"]), Some(64));
+    assert_eq!(code(&["--width", "3-1"]), Some(64));
+}
@@ -0,0 +1,87 @@
+//! Checks every implementation against every input in `benches/resources`,
+//! using the expected answer from its `.meta` sidecar (or `naive::pooled`'s, if it has none).
+//! Adding a regression case only takes dropping a file (and optionally its sidecar) into that directory.
+
+#[path = "support/resources.rs"]
+mod resources;
+
+use day13_compare::{limits::Limits, registry::IMPLEMENTATIONS};
+use resources::{resources, Meta};
+use std::panic::{self, AssertUnwindSafe};
+
+fn caught<T>(f: impl FnOnce() -> T) -> Result<T, String> {
+    panic::catch_unwind(AssertUnwindSafe(f)).map_err(|payload| {
+        payload
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_default()
+    })
+}
+
+#[test]
+fn every_implementation_matches_every_resource() {
+    let resources = resources().unwrap();
+    assert!(!resources.is_empty());
+    let mut failures = Vec::new();
+    for resource in &resources {
+        let name = &resource.name;
+        let input = match std::fs::read_to_string(&resource.path) {
+            Ok(input) => input,
+            Err(err) if resource.meta.invalid() => {
+                // not UTF-8, so the implementations never see it
+                assert_eq!(err.kind(), std::io::ErrorKind::InvalidData, "{name}");
+                continue;
+            }
+            Err(err) => panic!("{name}: {err}"),
+        };
+
+        if resource.meta.invalid() {
+            for implementation in IMPLEMENTATIONS.iter().filter(|i| i.validates) {
+                match caught(|| (implementation.try_day13)(&input, &Limits::UNBOUNDED)) {
+                    Ok(Err(_)) => {}
+                    other => failures.push(format!(
+                        "{name}: {} should reject it, got {other:?}",
+                        implementation.name
+                    )),
+                }
+            }
+            continue;
+        }
+
+        let expected = match resource.meta.answer {
+            Some(answer) => answer,
+            None => day13_compare::naive::pooled::day13(&input),
+        };
+        for implementation in IMPLEMENTATIONS {
+            let answer = caught(|| (implementation.day13)(&input));
+            if answer != Ok(expected) {
+                failures.push(format!(
+                    "{name}: {} returned {answer:?}, expected {expected}",
+                    implementation.name
+                ));
+            }
+            let answer = caught(|| (implementation.try_day13)(&input, &Limits::UNBOUNDED));
+            if answer != Ok(Ok(expected)) {
+                failures.push(format!(
+                    "{name}: {}'s try_day13 returned {answer:?}, expected {expected}",
+                    implementation.name
+                ));
+            }
+        }
+    }
+    assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+}
+
+#[test]
+fn sidecars_parse() {
+    let meta = Meta::parse("# a comment\n\nanswer = 13\ntags = deep, long-number,\n").unwrap();
+    assert_eq!(meta.answer, Some(13));
+    assert_eq!(meta.tags, ["deep", "long-number"]);
+    assert!(meta.has_tag("deep") && !meta.invalid());
+
+    assert_eq!(Meta::parse(""), Ok(Meta::default()));
+    assert!(Meta::parse("answer = thirteen").is_err());
+    assert!(Meta::parse("answer: 13").is_err());
+    assert!(Meta::parse("expected = 13").is_err());
+}
@@ -0,0 +1,16 @@
+//! The implementations the benchmarks time, by benchmark id: every one in the registry,
+//! then `input_handling_baseline`, which only reads the input, as the floor the others are measured against.
+//!
+//! Include it with `#[path]`.
+
+use day13_compare::{input_handling_baseline, registry};
+
+pub type Day13 = fn(&str) -> usize;
+
+pub fn benched() -> impl Iterator<Item = (&'static str, Day13)> {
+    let baseline: Day13 = input_handling_baseline::day13;
+    registry::IMPLEMENTATIONS
+        .iter()
+        .map(|implementation| (implementation.name, implementation.day13))
+        .chain([("input_handling_baseline", baseline)])
+}
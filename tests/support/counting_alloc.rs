@@ -0,0 +1,74 @@
+//! A global allocator that counts, per thread, the allocations made through it.
+//! Tests run on several threads at once, so the counts are thread-local,
+//! and each [measure] only sees what its own closure allocated.
+//!
+//! Include it with `#[path]` and register it with `#[global_allocator]`.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+pub struct CountingAlloc;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AllocStats {
+    /// `alloc` + `realloc` calls
+    pub allocations: usize,
+    /// bytes requested by those calls (a `realloc` counts its new size)
+    pub bytes: usize,
+    /// most bytes live at once, relative to the start of the measurement
+    pub peak_live: usize,
+}
+
+thread_local! {
+    static COUNTS: Cell<AllocStats> = const { Cell::new(AllocStats { allocations: 0, bytes: 0, peak_live: 0 }) };
+    // isize: memory allocated before a measurement may be freed during it
+    static LIVE: Cell<isize> = const { Cell::new(0) };
+}
+
+fn record(allocated: usize, freed: usize) {
+    // `try_with`: the allocator is still used while the thread locals are torn down
+    let _ = LIVE.try_with(|live| {
+        let now = live.get() + allocated as isize - freed as isize;
+        live.set(now);
+        let _ = COUNTS.try_with(|counts| {
+            let mut c = counts.get();
+            if allocated > 0 {
+                c.allocations += 1;
+                c.bytes += allocated;
+            }
+            c.peak_live = c.peak_live.max(now.max(0) as usize);
+            counts.set(c);
+        });
+    });
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        record(layout.size(), 0);
+        System.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        record(layout.size(), 0);
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        record(0, layout.size());
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        record(new_size, layout.size());
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+/// Runs `f` and returns what it allocated on this thread.
+/// Only meaningful if [CountingAlloc] is the `#[global_allocator]`.
+pub fn measure<R>(f: impl FnOnce() -> R) -> (R, AllocStats) {
+    COUNTS.with(|c| c.set(AllocStats::default()));
+    LIVE.with(|l| l.set(0));
+    let result = f();
+    (result, COUNTS.with(Cell::get))
+}
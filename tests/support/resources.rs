@@ -0,0 +1,135 @@
+//! The inputs in `benches/resources`, and their optional `<input>.meta` sidecars:
+//! ```text
+//! # the expected part A answer
+//! answer = 13
+//! tags = deep, long-number
+//! ```
+//! Blank lines and `#` comments are ignored, and both keys are optional.
+//! An `invalid` tag marks an input the validating implementations must reject; the benchmark skips those.
+//!
+//! Include it with `#[path]`.
+
+// each target that includes this uses a different part of it
+#![allow(dead_code)]
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+pub const SIDECAR_EXTENSION: &str = "meta";
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Meta {
+    pub answer: Option<usize>,
+    pub tags: Vec<String>,
+}
+
+impl Meta {
+    pub fn parse(text: &str) -> Result<Meta, String> {
+        let mut meta = Meta::default();
+        for (idx, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let line_no = idx + 1;
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(format!("line {line_no}: expected `key = value`"));
+            };
+            match key.trim() {
+                "answer" => {
+                    let answer = value.trim().parse().map_err(|_| {
+                        format!("line {line_no}: `{}` isn't an answer", value.trim())
+                    })?;
+                    meta.answer = Some(answer);
+                }
+                "tags" => meta.tags.extend(
+                    value
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|tag| !tag.is_empty())
+                        .map(str::to_owned),
+                ),
+                key => return Err(format!("line {line_no}: unknown key `{key}`")),
+            }
+        }
+        Ok(meta)
+    }
+
+    /// The sidecar of `input`, or the default if it has none
+    pub fn for_input(input: &Path) -> Result<Meta, String> {
+        let path = sidecar_path(input);
+        match fs::read_to_string(&path) {
+            Ok(text) => Meta::parse(&text).map_err(|err| format!("{}: {err}", path.display())),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Meta::default()),
+            Err(err) => Err(format!("{}: {err}", path.display())),
+        }
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
+
+    pub fn invalid(&self) -> bool {
+        self.has_tag("invalid")
+    }
+}
+
+/// `<input>.meta`, even if `input` has an extension of its own
+pub fn sidecar_path(input: &Path) -> PathBuf {
+    let mut path = input.as_os_str().to_owned();
+    path.push(".");
+    path.push(SIDECAR_EXTENSION);
+    PathBuf::from(path)
+}
+
+pub fn is_sidecar(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == SIDECAR_EXTENSION)
+}
+
+pub struct Resource {
+    pub path: PathBuf,
+    pub name: String,
+    pub meta: Meta,
+}
+
+pub fn dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("benches/resources")
+}
+
+/// Every input in [dir] (sorted by name) with its sidecar.
+/// A sidecar without an input, or one that doesn't parse, is an error.
+pub fn resources() -> Result<Vec<Resource>, String> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir())
+        .map_err(|err| err.to_string())?
+        .map(|entry| {
+            entry
+                .map(|entry| entry.path())
+                .map_err(|err| err.to_string())
+        })
+        .collect::<Result<_, _>>()?;
+    paths.retain(|path| path.is_file());
+    paths.sort();
+
+    let mut resources = Vec::new();
+    for path in &paths {
+        if is_sidecar(path) {
+            let input = path.with_extension("");
+            if !input.is_file() {
+                return Err(format!(
+                    "{}: there's no input named {}",
+                    path.display(),
+                    input.display()
+                ));
+            }
+            continue;
+        }
+        resources.push(Resource {
+            name: path.file_name().unwrap().to_string_lossy().into_owned(),
+            meta: Meta::for_input(path)?,
+            path: path.clone(),
+        });
+    }
+    Ok(resources)
+}
@@ -0,0 +1,217 @@
+//! Compares each benchmark's time with a saved baseline's (`--save-baseline <name>`), to catch regressions.
+//!
+//! A change counts when it's both significant, i.e. the two confidence intervals don't overlap,
+//! and larger than the benchmark's noise threshold, like criterion's `--noise-threshold` (2% by default).
+//! Short or allocation-heavy benchmarks tend to be noisier, so the threshold can be raised for just those.
+
+use crate::{
+    criterion::{Benchmark, Estimate},
+    table::{short, write_markdown_table},
+};
+use std::io::{self, Write};
+
+/// criterion's default `--noise-threshold`
+pub const DEFAULT_NOISE_THRESHOLD: f64 = 0.02;
+
+/// The noise threshold of each benchmark, as a fraction of its baseline time
+#[derive(Debug, Clone, PartialEq)]
+pub struct Thresholds {
+    default: f64,
+    /// benchmarks whose `<impl>/<input>` id contains the pattern; the last match wins
+    overrides: Vec<(String, f64)>,
+}
+
+impl Default for Thresholds {
+    fn default() -> Self {
+        Thresholds {
+            default: DEFAULT_NOISE_THRESHOLD,
+            overrides: Vec::new(),
+        }
+    }
+}
+
+impl Thresholds {
+    pub fn set_default(&mut self, threshold: f64) {
+        self.default = threshold;
+    }
+
+    pub fn set(&mut self, pattern: String, threshold: f64) {
+        self.overrides.push((pattern, threshold));
+    }
+
+    pub fn of(&self, id: &str) -> f64 {
+        self.overrides
+            .iter()
+            .rev()
+            .find(|(pattern, _)| id.contains(pattern.as_str()))
+            .map_or(self.default, |&(_, threshold)| threshold)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Regressed,
+    Improved,
+    /// within the noise threshold
+    Noise,
+    /// the confidence intervals overlap
+    Unchanged,
+}
+
+impl Verdict {
+    pub fn name(self) -> &'static str {
+        match self {
+            Verdict::Regressed => "REGRESSED",
+            Verdict::Improved => "improved",
+            Verdict::Noise => "within noise",
+            Verdict::Unchanged => "no change",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comparison {
+    /// `<impl>/<input>`
+    pub id: String,
+    /// nanoseconds per iteration
+    pub baseline: Estimate,
+    pub current: Estimate,
+    pub threshold: f64,
+    pub verdict: Verdict,
+}
+
+impl Comparison {
+    pub fn new(id: String, baseline: Estimate, current: Estimate, threshold: f64) -> Comparison {
+        let change = current.point / baseline.point - 1.0;
+        let verdict = if current.lower <= baseline.upper && current.upper >= baseline.lower {
+            Verdict::Unchanged
+        } else if change.abs() <= threshold {
+            Verdict::Noise
+        } else if change > 0.0 {
+            Verdict::Regressed
+        } else {
+            Verdict::Improved
+        };
+        Comparison {
+            id,
+            baseline,
+            current,
+            threshold,
+            verdict,
+        }
+    }
+
+    /// Relative change of the time, positive being slower
+    pub fn change(&self) -> f64 {
+        self.current.point / self.baseline.point - 1.0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Report {
+    /// in the current run's order
+    pub comparisons: Vec<Comparison>,
+    /// ids that only one side has, which can't regress
+    pub only_in_baseline: Vec<String>,
+    pub only_in_current: Vec<String>,
+}
+
+fn id_of(benchmark: &Benchmark) -> String {
+    format!("{}/{}", benchmark.function, benchmark.value)
+}
+
+impl Report {
+    pub fn new(baseline: &[Benchmark], current: &[Benchmark], thresholds: &Thresholds) -> Report {
+        let mut report = Report::default();
+        for benchmark in current {
+            let id = id_of(benchmark);
+            match baseline.iter().find(|b| id_of(b) == id) {
+                Some(base) => {
+                    let threshold = thresholds.of(&id);
+                    report.comparisons.push(Comparison::new(
+                        id,
+                        base.time,
+                        benchmark.time,
+                        threshold,
+                    ));
+                }
+                None => report.only_in_current.push(id),
+            }
+        }
+        report.only_in_baseline = baseline
+            .iter()
+            .map(id_of)
+            .filter(|id| !current.iter().any(|b| id_of(b) == *id))
+            .collect();
+        report
+    }
+
+    pub fn regressions(&self) -> usize {
+        self.count(Verdict::Regressed)
+    }
+
+    fn count(&self, verdict: Verdict) -> usize {
+        self.comparisons
+            .iter()
+            .filter(|comparison| comparison.verdict == verdict)
+            .count()
+    }
+
+    /// A Markdown table of every comparison, then what couldn't be compared and a summary
+    pub fn write(&self, out: &mut impl Write, baseline: &str, current: &str) -> io::Result<()> {
+        let header = [
+            "benchmark",
+            baseline,
+            current,
+            "change",
+            "threshold",
+            "verdict",
+        ]
+        .map(str::to_owned);
+        let rows: Vec<Vec<String>> = self
+            .comparisons
+            .iter()
+            .map(|c| {
+                vec![
+                    c.id.clone(),
+                    format_time(c.baseline),
+                    format_time(c.current),
+                    format!("{:+.2}%", c.change() * 100.0),
+                    format!("±{:.1}%", c.threshold * 100.0),
+                    c.verdict.name().to_owned(),
+                ]
+            })
+            .collect();
+        write_markdown_table(out, &header, &rows)?;
+        for (side, ids) in [
+            (baseline, &self.only_in_baseline),
+            (current, &self.only_in_current),
+        ] {
+            if !ids.is_empty() {
+                writeln!(out, "\nonly in `{side}`: {}", ids.join(", "))?;
+            }
+        }
+        writeln!(
+            out,
+            "\n{} regressed, {} improved, {} within noise, {} unchanged",
+            self.regressions(),
+            self.count(Verdict::Improved),
+            self.count(Verdict::Noise),
+            self.count(Verdict::Unchanged),
+        )
+    }
+}
+
+/// The point estimate and the confidence interval's half-width, like `1.234 µs ±0.8%`
+fn format_time(time: Estimate) -> String {
+    let (scale, unit) = [(1e9, "s"), (1e6, "ms"), (1e3, "µs")]
+        .into_iter()
+        .find(|&(scale, _)| time.point >= scale)
+        .unwrap_or((1.0, "ns"));
+    let spread = (time.upper - time.lower) / 2.0 / time.point;
+    format!(
+        "{} {unit} ±{:.1}%",
+        short(time.point / scale),
+        spread * 100.0
+    )
+}
@@ -0,0 +1,214 @@
+//! Reads the results criterion saves for each benchmark, under `target/criterion/<group>/<function>/<value>/<baseline>/`:
+//! `benchmark.json` names the benchmark and its throughput, and `estimates.json` has the statistics of its time per iteration.
+//! `<baseline>` is `new` for the latest run, or the name given to `--save-baseline`.
+//! `sample.json` has the raw samples, each an iteration count and their total time.
+
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+/// A point estimate and its confidence interval
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Estimate {
+    pub lower: f64,
+    pub point: f64,
+    pub upper: f64,
+}
+
+impl Estimate {
+    pub fn scaled(self, by: f64) -> Estimate {
+        Estimate {
+            lower: self.lower * by,
+            point: self.point * by,
+            upper: self.upper * by,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Benchmark {
+    /// the implementation, as spelled in the benchmark id, e.g. `naive::pooled`
+    pub function: String,
+    /// the input's name, or the swept parameter's value
+    pub value: String,
+    /// nanoseconds per iteration
+    pub time: Estimate,
+    /// bytes processed per iteration, if the group set a byte throughput
+    pub bytes: Option<u64>,
+}
+
+impl Benchmark {
+    /// Bytes per second; the lower bound comes from the upper bound of the time, and vice versa
+    pub fn throughput(&self) -> Option<Estimate> {
+        let bytes = self.bytes? as f64 * 1e9;
+        Some(Estimate {
+            lower: bytes / self.time.upper,
+            point: bytes / self.time.point,
+            upper: bytes / self.time.lower,
+        })
+    }
+}
+
+#[derive(Deserialize)]
+struct BenchmarkJson {
+    group_id: String,
+    function_id: Option<String>,
+    value_str: Option<String>,
+    throughput: Option<ThroughputJson>,
+}
+
+#[derive(Deserialize)]
+enum ThroughputJson {
+    Bytes(u64),
+    BytesDecimal(u64),
+    Elements(serde::de::IgnoredAny),
+}
+
+#[derive(Deserialize)]
+struct EstimatesJson {
+    mean: EstimateJson,
+    slope: Option<EstimateJson>,
+}
+
+#[derive(Deserialize)]
+struct SampleJson {
+    iters: Vec<f64>,
+    times: Vec<f64>,
+}
+
+#[derive(Deserialize)]
+struct EstimateJson {
+    confidence_interval: IntervalJson,
+    point_estimate: f64,
+}
+
+#[derive(Deserialize)]
+struct IntervalJson {
+    lower_bound: f64,
+    upper_bound: f64,
+}
+
+impl From<EstimateJson> for Estimate {
+    fn from(json: EstimateJson) -> Self {
+        Estimate {
+            lower: json.confidence_interval.lower_bound,
+            point: json.point_estimate,
+            upper: json.confidence_interval.upper_bound,
+        }
+    }
+}
+
+fn read_json<T: for<'de> Deserialize<'de>>(path: &Path) -> Result<T, String> {
+    let text = fs::read_to_string(path).map_err(|err| format!("{}: {err}", path.display()))?;
+    serde_json::from_str(&text).map_err(|err| format!("{}: {err}", path.display()))
+}
+
+/// The `<baseline>` directories below `dir`, skipping criterion's `report`s
+fn baseline_dirs(dir: &Path, baseline: &str, found: &mut Vec<PathBuf>) -> Result<(), String> {
+    let entries = fs::read_dir(dir).map_err(|err| format!("{}: {err}", dir.display()))?;
+    for entry in entries {
+        let path = entry.map_err(|err| err.to_string())?.path();
+        if !path.is_dir() || path.file_name().is_some_and(|name| name == "report") {
+            continue;
+        }
+        if path.file_name().is_some_and(|name| name == baseline)
+            && path.join("benchmark.json").is_file()
+        {
+            found.push(path);
+        } else {
+            baseline_dirs(&path, baseline, found)?;
+        }
+    }
+    Ok(())
+}
+
+/// Every benchmark of `group` in `baseline`, in the order they were last run (going by when criterion saved them).
+/// Like criterion's console output, the time is the slope estimate when criterion has one, and the mean otherwise.
+pub fn read_group(
+    criterion_dir: &Path,
+    group: &str,
+    baseline: &str,
+) -> Result<Vec<Benchmark>, String> {
+    read_benchmarks(criterion_dir, group, baseline, |dir| {
+        let estimates: EstimatesJson = read_json(&dir.join("estimates.json"))?;
+        Ok(estimates.slope.unwrap_or(estimates.mean).into())
+    })
+}
+
+/// Like [read_group], but with the spread of the samples' time per iteration instead of an estimate:
+/// the fastest sample's as the lower bound, the median's as the point, and the slowest's as the upper bound.
+pub fn read_group_spread(
+    criterion_dir: &Path,
+    group: &str,
+    baseline: &str,
+) -> Result<Vec<Benchmark>, String> {
+    read_benchmarks(criterion_dir, group, baseline, |dir| {
+        let path = dir.join("sample.json");
+        let sample: SampleJson = read_json(&path)?;
+        let mut times: Vec<f64> = sample
+            .times
+            .iter()
+            .zip(&sample.iters)
+            .map(|(time, iters)| time / iters)
+            .collect();
+        if times.is_empty() {
+            return Err(format!("{}: no samples", path.display()));
+        }
+        times.sort_by(f64::total_cmp);
+        Ok(Estimate {
+            lower: times[0],
+            point: times[times.len() / 2],
+            upper: times[times.len() - 1],
+        })
+    })
+}
+
+/// The benchmarks of `group` in `baseline`, in the order they were last run, with `time` read from their directory
+fn read_benchmarks(
+    criterion_dir: &Path,
+    group: &str,
+    baseline: &str,
+    time: impl Fn(&Path) -> Result<Estimate, String>,
+) -> Result<Vec<Benchmark>, String> {
+    let mut dirs = Vec::new();
+    baseline_dirs(criterion_dir, baseline, &mut dirs)?;
+
+    let mut benchmarks = Vec::new();
+    for dir in dirs {
+        let info_path = dir.join("benchmark.json");
+        let info: BenchmarkJson = read_json(&info_path)?;
+        if info.group_id != group {
+            continue;
+        }
+        let saved = fs::metadata(&info_path)
+            .and_then(|metadata| metadata.modified())
+            .unwrap_or(SystemTime::UNIX_EPOCH);
+        let bytes = match info.throughput {
+            Some(ThroughputJson::Bytes(bytes) | ThroughputJson::BytesDecimal(bytes)) => Some(bytes),
+            Some(ThroughputJson::Elements(_)) | None => None,
+        };
+        let benchmark = Benchmark {
+            function: info.function_id.unwrap_or_default(),
+            value: info.value_str.unwrap_or_default(),
+            time: time(&dir)?,
+            bytes,
+        };
+        benchmarks.push((saved, benchmark));
+    }
+    if benchmarks.is_empty() {
+        return Err(format!(
+            "no `{baseline}` results for the `{group}` group in {}",
+            criterion_dir.display()
+        ));
+    }
+    benchmarks.sort_by(|(a_saved, a), (b_saved, b)| {
+        (a_saved, &a.function, &a.value).cmp(&(b_saved, &b.function, &b.value))
+    });
+    Ok(benchmarks
+        .into_iter()
+        .map(|(_, benchmark)| benchmark)
+        .collect())
+}
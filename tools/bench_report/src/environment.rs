@@ -0,0 +1,229 @@
+//! The machine and build a run was measured with, as run times depend on both
+//! (see the README's "Benchmarks Disclaimer"): the CPU and its SIMD features from `/proc/cpuinfo`,
+//! the compiler from `rustc -vV`, the `[profile.bench]` / `[profile.release]` settings from `Cargo.toml`,
+//! and the environment variables that change the build or the benchmarks.
+//!
+//! `bench_report env --save` stores it next to criterion's results, one per baseline, as each run may have been measured
+//! on a different machine or build. The tables of a baseline then carry its environment:
+//! the TSV as trailing `# key = value` comment lines (which gnuplot skips, and whose `summary` the plots use as their title),
+//! and the Markdown as a last table.
+
+use crate::table::write_markdown_table;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// The directory in criterion's output directory that [Environment::save] writes into, a file per baseline
+pub const DIR_NAME: &str = "environment";
+
+/// `/proc/cpuinfo` flags (x86) or features (ARM) that are SIMD instruction sets, or close to them
+const SIMD_PREFIXES: [&str; 10] = [
+    "sse", "ssse", "avx", "fma", "bmi", "popcnt", "lzcnt", "asimd", "neon", "sve",
+];
+
+/// Variables that change the build or the benchmarks
+fn relevant(variable: &str) -> bool {
+    [
+        "RUSTFLAGS",
+        "RUSTC",
+        "RUSTC_WRAPPER",
+        "CARGO_ENCODED_RUSTFLAGS",
+    ]
+    .contains(&variable)
+        || ["CARGO_BUILD_", "CARGO_PROFILE_", "CARGO_TARGET_", "DAY13_"]
+            .iter()
+            .any(|prefix| variable.starts_with(prefix))
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Environment {
+    pub cpu: String,
+    /// logical cores
+    pub cores: usize,
+    pub simd: Vec<String>,
+    /// `rustc`'s version line, e.g. `rustc 1.72.0 (5680fa18f 2023-08-23)`
+    pub rustc: String,
+    pub llvm: String,
+    /// the host triple, which the benchmarks are built for unless `CARGO_BUILD_TARGET` says otherwise
+    pub target: String,
+    /// `<profile>.<key>=<value>` for each setting in the manifest's bench and release profiles
+    pub profile: Vec<String>,
+    pub variables: BTreeMap<String, String>,
+    /// the size of the whole environment block, which shifts the stack and so the alignment of everything on it
+    pub environment_bytes: usize,
+}
+
+impl Environment {
+    /// The current machine's, with the profiles in `manifest` (if it can be read).
+    /// Whatever can't be found stays empty.
+    pub fn collect(manifest: &Path) -> Environment {
+        let mut environment = Environment::default();
+
+        let cpuinfo = fs::read_to_string("/proc/cpuinfo").unwrap_or_default();
+        let field = |name: &str| {
+            cpuinfo.lines().find_map(|line| {
+                let (key, value) = line.split_once(':')?;
+                (key.trim() == name).then(|| value.trim().to_owned())
+            })
+        };
+        environment.cpu = field("model name").unwrap_or_default();
+        environment.cores = cpuinfo
+            .lines()
+            .filter(|line| line.starts_with("processor"))
+            .count();
+        if environment.cores == 0 {
+            environment.cores = std::thread::available_parallelism().map_or(0, usize::from);
+        }
+        environment.simd = field("flags")
+            .or_else(|| field("Features"))
+            .unwrap_or_default()
+            .split_whitespace()
+            .filter(|flag| SIMD_PREFIXES.iter().any(|prefix| flag.starts_with(prefix)))
+            .map(str::to_owned)
+            .collect();
+
+        let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_owned());
+        if let Ok(output) = Command::new(rustc).arg("-vV").output() {
+            let version = String::from_utf8_lossy(&output.stdout);
+            for line in version.lines() {
+                match line.split_once(": ") {
+                    Some(("host", host)) => environment.target = host.to_owned(),
+                    Some(("LLVM version", llvm)) => environment.llvm = llvm.to_owned(),
+                    Some(_) => {}
+                    None => environment.rustc = line.to_owned(),
+                }
+            }
+        }
+
+        environment.profile = fs::read_to_string(manifest)
+            .map(|manifest| profile_settings(&manifest))
+            .unwrap_or_default();
+
+        for (variable, value) in std::env::vars_os() {
+            environment.environment_bytes += variable.len() + value.len() + 2;
+            let variable = variable.to_string_lossy();
+            if relevant(&variable) {
+                environment
+                    .variables
+                    .insert(variable.into_owned(), value.to_string_lossy().into_owned());
+            }
+        }
+        if let Some(target) = environment.variables.get("CARGO_BUILD_TARGET") {
+            environment.target = target.clone();
+        }
+        environment
+    }
+
+    /// Where [Environment::save] stores the environment of `baseline` (a name given to `--save-baseline`,
+    /// or `new` for the latest run) in criterion's output directory `dir`
+    pub fn path(dir: &Path, baseline: &str) -> PathBuf {
+        dir.join(DIR_NAME).join(format!("{baseline}.json"))
+    }
+
+    /// Saved for `baseline` into criterion's output directory `dir` by [Environment::save]
+    pub fn load(dir: &Path, baseline: &str) -> Result<Option<Environment>, String> {
+        let path = Environment::path(dir, baseline);
+        match fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json)
+                .map(Some)
+                .map_err(|err| format!("{}: {err}", path.display())),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(format!("{}: {err}", path.display())),
+        }
+    }
+
+    pub fn save(&self, dir: &Path, baseline: &str) -> io::Result<()> {
+        let path = Environment::path(dir, baseline);
+        fs::create_dir_all(path.parent().unwrap())?;
+        fs::write(path, serde_json::to_string_pretty(self)?)
+    }
+
+    /// One line, for a plot's title
+    pub fn summary(&self) -> String {
+        let mut summary = format!(
+            "{}, {} cores, {}, {}",
+            self.cpu, self.cores, self.rustc, self.target
+        );
+        if let Some(flags) = self.variables.get("RUSTFLAGS") {
+            summary.push_str(&format!(", RUSTFLAGS={flags}"));
+        }
+        summary
+    }
+
+    /// `(key, value)`, with the summary first
+    pub fn fields(&self) -> Vec<(String, String)> {
+        let mut fields = vec![
+            ("summary".to_owned(), self.summary()),
+            ("cpu".to_owned(), self.cpu.clone()),
+            ("cores".to_owned(), self.cores.to_string()),
+            ("simd".to_owned(), self.simd.join(", ")),
+            ("rustc".to_owned(), self.rustc.clone()),
+            ("llvm".to_owned(), self.llvm.clone()),
+            ("target".to_owned(), self.target.clone()),
+            (
+                "profile".to_owned(),
+                if self.profile.is_empty() {
+                    "default".to_owned()
+                } else {
+                    self.profile.join(", ")
+                },
+            ),
+            (
+                "environment bytes".to_owned(),
+                self.environment_bytes.to_string(),
+            ),
+        ];
+        fields.extend(
+            self.variables
+                .iter()
+                .map(|(variable, value)| (format!("env {variable}"), value.clone())),
+        );
+        fields
+    }
+
+    /// `key = value` lines, each starting with `prefix`
+    pub fn write_text(&self, out: &mut impl Write, prefix: &str) -> io::Result<()> {
+        for (key, value) in self.fields() {
+            writeln!(out, "{prefix}{key} = {value}")?;
+        }
+        Ok(())
+    }
+
+    pub fn write_markdown(&self, out: &mut impl Write) -> io::Result<()> {
+        let header = ["environment".to_owned(), String::new()];
+        let rows: Vec<Vec<String>> = self
+            .fields()
+            .into_iter()
+            .skip(1)
+            .map(|(key, value)| vec![key, value])
+            .collect();
+        write_markdown_table(out, &header, &rows)
+    }
+}
+
+/// The settings in the `[profile.bench]` and `[profile.release]` sections of a manifest,
+/// read line by line (which is all the repo's manifest needs)
+pub fn profile_settings(manifest: &str) -> Vec<String> {
+    let mut settings = Vec::new();
+    let mut profile = None;
+    for line in manifest.lines() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line.starts_with('[') {
+            profile = match line {
+                "[profile.bench]" => Some("bench"),
+                "[profile.release]" => Some("release"),
+                _ => None,
+            };
+            continue;
+        }
+        if let (Some(profile), Some((key, value))) = (profile, line.split_once('=')) {
+            settings.push(format!("{profile}.{}={}", key.trim(), value.trim()));
+        }
+    }
+    settings
+}
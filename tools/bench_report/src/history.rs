@@ -0,0 +1,239 @@
+//! A local history of benchmark runs, to follow each implementation's throughput as the code evolves.
+//!
+//! Each run appends one JSON line per benchmark to the history file, tagged with the commit and the time of the run:
+//! ```text
+//! {"commit":"5af5d8b","timestamp":1697500000,"group":"Day13_A","function":"logos_lex","value":"original sample","time":{"lower":301.2,"point":302.5,"upper":303.9},"bytes":186}
+//! ```
+//! [Trend] then lays out one input's throughput with a row per run, for `scripts/thrpt_line_chart.gp`.
+
+use crate::{
+    criterion::{Benchmark, Estimate},
+    environment::Environment,
+    table::{in_unit, unit_of, UNITS},
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    io::{self, BufRead, BufReader, Write},
+    path::Path,
+};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Entry {
+    /// with a `-dirty` suffix if the working tree had uncommitted changes
+    pub commit: String,
+    /// seconds since the Unix epoch
+    pub timestamp: u64,
+    pub group: String,
+    #[serde(flatten)]
+    pub benchmark: Benchmark,
+    /// what the run was measured with, if it was known when it was recorded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub environment: Option<Environment>,
+}
+
+/// A run is the entries recorded together, which share a commit, a timestamp and an environment
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Run {
+    pub commit: String,
+    pub timestamp: u64,
+    /// the environment's [summary](Environment::summary)
+    pub environment: Option<String>,
+}
+
+impl Run {
+    fn of(entry: &Entry) -> Run {
+        Run {
+            commit: entry.commit.clone(),
+            timestamp: entry.timestamp,
+            environment: entry.environment.as_ref().map(Environment::summary),
+        }
+    }
+
+    /// `<commit> <date> <time>`, in UTC
+    pub fn label(&self) -> String {
+        format!("{} {}", self.commit, utc(self.timestamp))
+    }
+}
+
+/// `YYYY-MM-DD HH:MM`, see http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+fn utc(timestamp: u64) -> String {
+    let (days, seconds) = (timestamp / 86_400, timestamp % 86_400);
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let day_of_era = z % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}",
+        seconds / 3600,
+        seconds % 3600 / 60
+    )
+}
+
+/// Appends `benchmarks` to the history at `path` (creating it if needed) as one run
+pub fn append(
+    path: &Path,
+    commit: &str,
+    timestamp: u64,
+    environment: Option<&Environment>,
+    group: &str,
+    benchmarks: &[Benchmark],
+) -> io::Result<()> {
+    let mut out = io::BufWriter::new(fs::File::options().create(true).append(true).open(path)?);
+    for benchmark in benchmarks {
+        let entry = Entry {
+            commit: commit.to_owned(),
+            timestamp,
+            group: group.to_owned(),
+            benchmark: benchmark.clone(),
+            environment: environment.cloned(),
+        };
+        serde_json::to_writer(&mut out, &entry)?;
+        writeln!(out)?;
+    }
+    out.flush()
+}
+
+/// Every entry in the history at `path`, oldest first
+pub fn read(path: &Path) -> Result<Vec<Entry>, String> {
+    let file = fs::File::open(path).map_err(|err| format!("{}: {err}", path.display()))?;
+    let mut entries = Vec::new();
+    for (idx, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.map_err(|err| format!("{}: {err}", path.display()))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let entry = serde_json::from_str(&line)
+            .map_err(|err| format!("{}:{}: {err}", path.display(), idx + 1))?;
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+/// One input's throughput, with a row per run and a column per implementation.
+/// An implementation that didn't run (or had no byte throughput) in a run has a gap there.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trend {
+    pub input: String,
+    pub runs: Vec<Run>,
+    /// in the order they first appear
+    pub implementations: Vec<String>,
+    /// bytes per second, `rows[run][implementation]`
+    pub rows: Vec<Vec<Option<Estimate>>>,
+}
+
+impl Trend {
+    /// A trend for each input of `group`, in the order they first appear
+    pub fn of_group(entries: &[Entry], group: &str) -> Vec<Trend> {
+        let entries: Vec<&Entry> = entries.iter().filter(|e| e.group == group).collect();
+        let mut runs: Vec<Run> = Vec::new();
+        let mut trends: Vec<Trend> = Vec::new();
+        for entry in &entries {
+            let run = Run::of(entry);
+            if runs.last() != Some(&run) {
+                runs.push(run);
+            }
+            let input = &entry.benchmark.value;
+            if !trends.iter().any(|trend| &trend.input == input) {
+                trends.push(Trend {
+                    input: input.clone(),
+                    runs: Vec::new(),
+                    implementations: Vec::new(),
+                    rows: Vec::new(),
+                });
+            }
+        }
+        for trend in &mut trends {
+            let input_entries = || entries.iter().filter(|e| e.benchmark.value == trend.input);
+            for entry in input_entries() {
+                if !trend.implementations.contains(&entry.benchmark.function) {
+                    trend.implementations.push(entry.benchmark.function.clone());
+                }
+            }
+            for run in &runs {
+                let run_entries: Vec<&&Entry> =
+                    input_entries().filter(|e| Run::of(e) == *run).collect();
+                if run_entries.is_empty() {
+                    continue;
+                }
+                let row = trend
+                    .implementations
+                    .iter()
+                    .map(|implementation| {
+                        run_entries
+                            .iter()
+                            .find(|e| &e.benchmark.function == implementation)
+                            .and_then(|e| e.benchmark.throughput())
+                    })
+                    .collect();
+                trend.runs.push(run.clone());
+                trend.rows.push(row);
+            }
+        }
+        trends
+    }
+
+    /// In the format of `bench_report tsv --sweep`, with the runs' labels as the first column
+    /// and `NaN` for the gaps, which gnuplot leaves out.
+    /// Each run's environment follows as a `# <label> = <summary>` comment line.
+    pub fn write_tsv(&self, out: &mut impl Write) -> io::Result<()> {
+        let unit = self
+            .rows
+            .iter()
+            .flatten()
+            .flatten()
+            .map(|e| unit_of(e.lower))
+            .min()
+            .unwrap_or(0);
+        write!(out, "{}", UNITS[unit])?;
+        for implementation in &self.implementations {
+            write!(out, "\tlow\t{implementation}\thigh")?;
+        }
+        writeln!(out)?;
+        for (run, row) in self.runs.iter().zip(&self.rows) {
+            write!(out, "{}", run.label())?;
+            for estimate in row {
+                match *estimate {
+                    Some(estimate) => {
+                        let Estimate {
+                            lower,
+                            point,
+                            upper,
+                        } = in_unit(estimate, unit);
+                        write!(out, "\t{lower}\t{point}\t{upper}")?;
+                    }
+                    None => write!(out, "\tNaN\tNaN\tNaN")?,
+                }
+            }
+            writeln!(out)?;
+        }
+        for run in &self.runs {
+            if let Some(environment) = &run.environment {
+                writeln!(out, "# {} = {environment}", run.label())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The input's name, with anything but ASCII letters, digits, `-` and `_` replaced
+    pub fn file_name(&self) -> String {
+        let stem: String = self
+            .input
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        format!("{stem}.data")
+    }
+}
@@ -0,0 +1,10 @@
+//! Reads the results criterion saves under `target/criterion` (see [criterion])
+//! and writes them as the tables the gnuplot scripts and the README use (see [table]),
+//! compares them with a saved baseline (see [compare]), or keeps a history of them (see [history]),
+//! along with the machine and build they were measured with (see [environment]).
+
+pub mod compare;
+pub mod criterion;
+pub mod environment;
+pub mod history;
+pub mod table;
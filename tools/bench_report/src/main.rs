@@ -0,0 +1,401 @@
+//! Turns the results criterion saved under `target/criterion` into tables, see `bench_report::table`,
+//! compares them with a saved baseline, see `bench_report::compare`,
+//! or keeps a history of them, see `bench_report::history`.
+//! Each carries the environment the results were measured in, see `bench_report::environment`.
+//!
+//! usage: bench_report tsv [--sweep] [--spread] [<option>...]
+//!        bench_report markdown [--normalize <implementation>] [<option>...]
+//!        bench_report spread [<option>...]
+//!        bench_report compare <baseline> [--noise [<text>=]<percent>]... [<option>...]
+//!        bench_report record [--commit <id>] [--history <path>] [<option>...]
+//!        bench_report trend [--out <dir>] [--history <path>] [<option>...]
+//!        bench_report env [--save] [--baseline <name>] [--dir <path>]
+//!
+//! Exits with 1 if `compare` finds a regression.
+//! Otherwise, exit codes follow sysexits.h: 64 for bad arguments, 65 for missing or unreadable results, 74 for I/O errors.
+
+use bench_report::{
+    compare::{Report, Thresholds},
+    criterion,
+    environment::Environment,
+    history::{self, Trend},
+    table::Table,
+};
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process::{self, ExitCode},
+    time::SystemTime,
+};
+
+const USAGE: &str = "usage: bench_report tsv [--sweep] [--spread] [<option>...]
+       bench_report markdown [--normalize <implementation>] [<option>...]
+       bench_report spread [<option>...]
+       bench_report compare <baseline> [--noise [<text>=]<percent>]... [<option>...]
+       bench_report record [--commit <id>] [--history <path>] [<option>...]
+       bench_report trend [--out <dir>] [--history <path>] [<option>...]
+       bench_report env [--save] [--baseline <name>] [--dir <path>]
+
+tsv and markdown write the throughput of each implementation (columns) on each input (rows) to stdout,
+as the TSV the gnuplot scripts read, or as Markdown tables.
+spread writes the same as markdown, with how far the samples' throughput spread around their median, see benches/alignment.rs.
+compare reports how the time of each benchmark changed since <baseline> (a name given to --save-baseline),
+and exits with 1 if any regressed.
+record appends the results to the history file, as a run of the current commit (with -dirty if the tree has changes).
+trend writes a table per input to <dir> with the throughput in each run in the history, see scripts/plot_trends.sh.
+env prints the machine and build environment, and with --save, stores it with criterion's results for the other commands,
+as the environment of --baseline; they say the environment is unknown if none was stored for the baseline they report.
+--dir <path>        criterion's output directory (default: target/criterion)
+--group <name>      the benchmark group (default: Day13_A)
+--baseline <name>   the results to report, a name given to --save-baseline (default: new, the latest run)
+--filter <text>     only the implementations whose name contains <text>
+--sweep             (tsv) one unit for the whole table, and the input names as-is, see scripts/thrpt_line_chart.gp
+--spread            (tsv) the slowest, median and fastest sample instead of the confidence interval
+--normalize <impl>  (markdown) also a table relative to <impl>, with the geometric mean of each column
+--noise <percent>   (compare) the change to ignore, even if significant (default: 2)
+--noise <text>=<percent>
+                    (compare) the same, for the benchmarks whose <impl>/<input> contains <text>; the last match wins
+--commit <id>       (record) instead of asking git
+--history <path>    (record, trend) the history file (default: bench_history.jsonl)
+--out <dir>         (trend) where to write the tables (default: target/trend)
+--save              (env) write it to <dir>/environment/<baseline>.json";
+
+const EXIT_USAGE: u8 = 64;
+const EXIT_DATA: u8 = 65;
+const EXIT_IO: u8 = 74;
+
+enum Command {
+    Tsv {
+        sweep: bool,
+        spread: bool,
+    },
+    Markdown {
+        normalize: Option<String>,
+    },
+    Spread,
+    Compare {
+        baseline: String,
+        thresholds: Thresholds,
+    },
+    Record {
+        commit: Option<String>,
+        history: PathBuf,
+    },
+    Trend {
+        out: PathBuf,
+        history: PathBuf,
+    },
+    Env {
+        save: bool,
+    },
+}
+
+const DEFAULT_HISTORY: &str = "bench_history.jsonl";
+
+struct Args {
+    command: Command,
+    dir: PathBuf,
+    group: String,
+    baseline: String,
+    filter: Option<String>,
+}
+
+fn parse_percent(flag: &str, value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(percent) if percent >= 0.0 => Ok(percent / 100.0),
+        _ => Err(format!("invalid percentage `{value}` for {flag}")),
+    }
+}
+
+/// `None` if there's nothing to report
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Option<Args>, String> {
+    let command = match args.next().as_deref() {
+        Some("tsv") => Command::Tsv {
+            sweep: false,
+            spread: false,
+        },
+        Some("markdown") => Command::Markdown { normalize: None },
+        Some("spread") => Command::Spread,
+        Some("compare") => Command::Compare {
+            baseline: args.next().ok_or("compare needs a baseline")?,
+            thresholds: Thresholds::default(),
+        },
+        Some("record") => Command::Record {
+            commit: None,
+            history: PathBuf::from(DEFAULT_HISTORY),
+        },
+        Some("trend") => Command::Trend {
+            out: PathBuf::from("target/trend"),
+            history: PathBuf::from(DEFAULT_HISTORY),
+        },
+        Some("env") => Command::Env { save: false },
+        Some("-h" | "--help") => {
+            println!("{USAGE}");
+            return Ok(None);
+        }
+        Some(other) => {
+            return Err(format!(
+                "unknown command `{other}`, expected tsv, markdown, spread, compare, record, trend or env"
+            ))
+        }
+        None => {
+            return Err(
+                "expected a command, tsv, markdown, spread, compare, record, trend or env".to_owned(),
+            )
+        }
+    };
+    let mut parsed = Args {
+        command,
+        dir: PathBuf::from("target/criterion"),
+        group: "Day13_A".to_owned(),
+        baseline: "new".to_owned(),
+        filter: None,
+    };
+    while let Some(arg) = args.next() {
+        let flag = arg.as_str();
+        let mut value = || args.next().ok_or_else(|| format!("{flag} needs a value"));
+        match (flag, &mut parsed.command) {
+            ("-h" | "--help", _) => {
+                println!("{USAGE}");
+                return Ok(None);
+            }
+            ("--dir", _) => parsed.dir = PathBuf::from(value()?),
+            ("--group", _) => parsed.group = value()?,
+            ("--baseline", _) => parsed.baseline = value()?,
+            ("--filter", _) => parsed.filter = Some(value()?),
+            ("--sweep", Command::Tsv { sweep, .. }) => *sweep = true,
+            ("--spread", Command::Tsv { spread, .. }) => *spread = true,
+            ("--normalize", Command::Markdown { normalize }) => *normalize = Some(value()?),
+            ("--noise", Command::Compare { thresholds, .. }) => {
+                let value = value()?;
+                match value.rsplit_once('=') {
+                    Some((pattern, percent)) => {
+                        thresholds.set(pattern.to_owned(), parse_percent(flag, percent)?)
+                    }
+                    None => thresholds.set_default(parse_percent(flag, &value)?),
+                }
+            }
+            ("--commit", Command::Record { commit, .. }) => *commit = Some(value()?),
+            ("--history", Command::Record { history, .. } | Command::Trend { history, .. }) => {
+                *history = PathBuf::from(value()?)
+            }
+            ("--out", Command::Trend { out, .. }) => *out = PathBuf::from(value()?),
+            ("--save", Command::Env { save }) => *save = true,
+            _ => return Err(format!("unexpected argument `{arg}`")),
+        }
+    }
+    Ok(Some(parsed))
+}
+
+fn read(args: &Args, baseline: &str) -> Result<Vec<criterion::Benchmark>, String> {
+    let mut benchmarks = match args.command {
+        Command::Tsv { spread: true, .. } | Command::Spread => {
+            criterion::read_group_spread(&args.dir, &args.group, baseline)?
+        }
+        _ => criterion::read_group(&args.dir, &args.group, baseline)?,
+    };
+    if let Some(filter) = &args.filter {
+        benchmarks.retain(|benchmark| benchmark.function.contains(filter.as_str()));
+        if benchmarks.is_empty() {
+            return Err(format!("no implementation matches `{filter}`"));
+        }
+    }
+    Ok(benchmarks)
+}
+
+/// The short id of the checked out commit, with `-dirty` if tracked files have changes
+fn git_commit() -> Option<String> {
+    let git = |args: &[&str]| {
+        process::Command::new("git")
+            .args(args)
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_owned())
+    };
+    let commit = git(&["rev-parse", "--short", "HEAD"])?;
+    let dirty = !git(&["status", "--porcelain", "--untracked-files=no"])?.is_empty();
+    Some(if dirty {
+        format!("{commit}-dirty")
+    } else {
+        commit
+    })
+}
+
+/// Writes a table per input of the group in `history` to `dir`, and lists them to `out`
+fn write_trends(
+    args: &Args,
+    out: &mut impl Write,
+    dir: &Path,
+    history: &Path,
+) -> Result<io::Result<()>, String> {
+    let mut entries = history::read(history)?;
+    if let Some(filter) = &args.filter {
+        entries.retain(|entry| entry.benchmark.function.contains(filter.as_str()));
+    }
+    let trends = Trend::of_group(&entries, &args.group);
+    if trends.is_empty() {
+        return Err(format!(
+            "{} has no runs of the `{}` group",
+            history.display(),
+            args.group
+        ));
+    }
+    Ok(fs::create_dir_all(dir).and_then(|()| {
+        for trend in trends {
+            let path = dir.join(trend.file_name());
+            let mut file = io::BufWriter::new(fs::File::create(&path)?);
+            trend.write_tsv(&mut file)?;
+            file.flush()?;
+            writeln!(out, "{}\t{}", path.display(), trend.input)?;
+        }
+        Ok(())
+    }))
+}
+
+/// The one `env --save` stored for the reported baseline, if any
+fn environment(args: &Args) -> Result<Option<Environment>, String> {
+    Environment::load(&args.dir, &args.baseline)
+}
+
+/// What's written in place of the environment, if none was stored
+const UNKNOWN_ENVIRONMENT: &str = "environment: unknown";
+
+/// `environment` as `# key = value` comment lines
+fn write_environment_comments(
+    out: &mut impl Write,
+    environment: Option<&Environment>,
+) -> io::Result<()> {
+    match environment {
+        Some(environment) => environment.write_text(out, "# "),
+        None => writeln!(out, "# {UNKNOWN_ENVIRONMENT}"),
+    }
+}
+
+/// `environment` as a Markdown table
+fn write_environment_markdown(
+    out: &mut impl Write,
+    environment: Option<&Environment>,
+) -> io::Result<()> {
+    match environment {
+        Some(environment) => environment.write_markdown(out),
+        None => writeln!(out, "{UNKNOWN_ENVIRONMENT}"),
+    }
+}
+
+/// Whether the results pass, i.e. nothing regressed
+fn report(args: &Args, out: &mut impl Write) -> Result<io::Result<bool>, String> {
+    let benchmarks = || read(args, &args.baseline);
+    let written = match &args.command {
+        Command::Tsv { sweep, .. } => {
+            let table = Table::throughput(&benchmarks()?)?;
+            let environment = environment(args)?;
+            table
+                .write_tsv(out, *sweep)
+                .and_then(|()| write_environment_comments(out, environment.as_ref()))
+        }
+        Command::Markdown { normalize } => {
+            let table = Table::throughput(&benchmarks()?)?;
+            let normalize_by = match normalize {
+                Some(name) => Some(
+                    table
+                        .implementations
+                        .iter()
+                        .position(|implementation| {
+                            implementation.replace(' ', "") == name.replace(' ', "")
+                        })
+                        .ok_or_else(|| format!("`{name}` isn't in the table"))?,
+                ),
+                None => None,
+            };
+            let environment = environment(args)?;
+            table
+                .write_markdown(out, normalize_by)
+                .and_then(|()| writeln!(out))
+                .and_then(|()| write_environment_markdown(out, environment.as_ref()))
+        }
+        Command::Spread => {
+            let table = Table::throughput(&benchmarks()?)?;
+            let environment = environment(args)?;
+            table
+                .write_spread_markdown(out)
+                .and_then(|()| writeln!(out))
+                .and_then(|()| write_environment_markdown(out, environment.as_ref()))
+        }
+        Command::Compare {
+            baseline,
+            thresholds,
+        } => {
+            let report = Report::new(&read(args, baseline)?, &benchmarks()?, thresholds);
+            return Ok(report
+                .write(out, baseline, &args.baseline)
+                .map(|()| report.regressions() == 0));
+        }
+        Command::Record { commit, history } => {
+            let benchmarks = benchmarks()?;
+            let commit = commit
+                .clone()
+                .or_else(git_commit)
+                .ok_or("couldn't get the commit from git, pass --commit")?;
+            let timestamp = SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .map_or(0, |since| since.as_secs());
+            let environment = environment(args)?;
+            history::append(
+                history,
+                &commit,
+                timestamp,
+                environment.as_ref(),
+                &args.group,
+                &benchmarks,
+            )
+            .and_then(|()| {
+                writeln!(
+                    out,
+                    "recorded {} benchmarks of {commit} to {}",
+                    benchmarks.len(),
+                    history.display()
+                )
+            })
+        }
+        Command::Trend { out: dir, history } => write_trends(args, out, dir, history)?,
+        Command::Env { save } => {
+            let environment = Environment::collect(Path::new("Cargo.toml"));
+            let saved = if *save {
+                environment.save(&args.dir, &args.baseline)
+            } else {
+                Ok(())
+            };
+            saved.and_then(|()| environment.write_text(out, ""))
+        }
+    };
+    Ok(written.map(|()| true))
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(Some(args)) => args,
+        Ok(None) => return ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("error: {message}\n\n{USAGE}");
+            return ExitCode::from(EXIT_USAGE);
+        }
+    };
+    let mut stdout = io::stdout().lock();
+    let passed = match report(&args, &mut stdout) {
+        Ok(passed) => passed.and_then(|passed| stdout.flush().map(|()| passed)),
+        Err(message) => {
+            eprintln!("error: {message}");
+            return ExitCode::from(EXIT_DATA);
+        }
+    };
+    match passed {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(err) => {
+            eprintln!("error: couldn't write the output: {err}");
+            ExitCode::from(EXIT_IO)
+        }
+    }
+}
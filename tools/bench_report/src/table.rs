@@ -0,0 +1,266 @@
+//! Throughput by input (rows) and implementation (columns),
+//! written as the TSV the gnuplot scripts read or as the Markdown tables in the README.
+
+use crate::criterion::{Benchmark, Estimate};
+use std::io::{self, Write};
+
+/// criterion's (binary) throughput units
+pub const UNITS: [&str; 5] = ["B/s", "KiB/s", "MiB/s", "GiB/s", "TiB/s"];
+
+/// The unit criterion would print `bytes_per_sec` in
+pub fn unit_of(bytes_per_sec: f64) -> usize {
+    let mut unit = 0;
+    while unit + 1 < UNITS.len() && bytes_per_sec >= 1024f64.powi(unit as i32 + 1) {
+        unit += 1;
+    }
+    unit
+}
+
+pub(crate) fn in_unit(estimate: Estimate, unit: usize) -> Estimate {
+    estimate.scaled(1024f64.powi(-(unit as i32)))
+}
+
+/// About 4 significant digits, like the README's tables
+pub(crate) fn short(value: f64) -> String {
+    if value < 10.0 {
+        format!("{value:.3}")
+    } else if value < 100.0 {
+        format!("{value:.2}")
+    } else if value < 1000.0 {
+        format!("{value:.1}")
+    } else {
+        format!("{value:.0}")
+    }
+}
+
+pub struct Table {
+    /// in the order they were first run
+    pub implementations: Vec<String>,
+    pub inputs: Vec<String>,
+    /// bytes per second, `rows[input][implementation]`
+    pub rows: Vec<Vec<Estimate>>,
+}
+
+impl Table {
+    /// Every implementation needs a result on every input, and every benchmark a byte throughput
+    pub fn throughput(benchmarks: &[Benchmark]) -> Result<Table, String> {
+        let mut implementations: Vec<String> = Vec::new();
+        let mut inputs: Vec<String> = Vec::new();
+        for benchmark in benchmarks {
+            if !implementations.contains(&benchmark.function) {
+                implementations.push(benchmark.function.clone());
+            }
+            if !inputs.contains(&benchmark.value) {
+                inputs.push(benchmark.value.clone());
+            }
+        }
+        let rows = inputs
+            .iter()
+            .map(|input| {
+                implementations
+                    .iter()
+                    .map(|implementation| {
+                        let benchmark = benchmarks
+                            .iter()
+                            .find(|b| &b.function == implementation && &b.value == input)
+                            .ok_or_else(|| {
+                                format!("no result for `{implementation}` on `{input}`")
+                            })?;
+                        benchmark.throughput().ok_or_else(|| {
+                            format!("`{implementation}` on `{input}` has no byte throughput")
+                        })
+                    })
+                    .collect()
+            })
+            .collect::<Result<_, String>>()?;
+        Ok(Table {
+            implementations,
+            inputs,
+            rows,
+        })
+    }
+
+    /// The unit of each row: that of its lowest value, or of the whole table's lowest value if `shared`
+    fn units(&self, shared: bool) -> Vec<usize> {
+        let row_units: Vec<usize> = self
+            .rows
+            .iter()
+            .map(|row| row.iter().map(|e| unit_of(e.lower)).min().unwrap_or(0))
+            .collect();
+        match row_units.iter().min() {
+            Some(&lowest) if shared => vec![lowest; row_units.len()],
+            _ => row_units,
+        }
+    }
+
+    /// One row per input, with a low, mid and high column per implementation.
+    /// Each row is in the unit of its lowest value, which is appended to the input's name.
+    /// With `sweep`, the input names (the parameter's values) are left as-is, so they can serve as x values,
+    /// and the whole table shares one unit, which is the first header field.
+    pub fn write_tsv(&self, out: &mut impl Write, sweep: bool) -> io::Result<()> {
+        let units = self.units(sweep);
+        let title = match units.first() {
+            Some(&unit) if sweep => UNITS[unit],
+            _ => "Title",
+        };
+        write!(out, "{title}")?;
+        for implementation in &self.implementations {
+            write!(out, "\tlow\t{implementation}\thigh")?;
+        }
+        writeln!(out)?;
+        for ((input, row), &unit) in self.inputs.iter().zip(&self.rows).zip(&units) {
+            if sweep {
+                write!(out, "{input}")?;
+            } else {
+                write!(out, "{input} ({})", UNITS[unit])?;
+            }
+            for &estimate in row {
+                let Estimate {
+                    lower,
+                    point,
+                    upper,
+                } = in_unit(estimate, unit);
+                write!(out, "\t{lower}\t{point}\t{upper}")?;
+            }
+            writeln!(out)?;
+        }
+        Ok(())
+    }
+
+    /// A legend numbering the implementations, then the throughput with those numbers as the column headers.
+    /// With `normalize_by` (an index into [Table::implementations]), also the throughput relative to that
+    /// implementation's, with the geometric mean of each column as the last row.
+    pub fn write_markdown(
+        &self,
+        out: &mut impl Write,
+        normalize_by: Option<usize>,
+    ) -> io::Result<()> {
+        let ids = self.write_legend(out)?;
+
+        let mut header = vec!["input_name \\ impl name".to_owned()];
+        header.extend(ids.iter().cloned());
+        let mut thrpt_header = header.clone();
+        thrpt_header.push("thrpt unit".to_owned());
+        let units = self.units(false);
+        let rows: Vec<Vec<String>> = self
+            .inputs
+            .iter()
+            .zip(&self.rows)
+            .zip(&units)
+            .map(|((input, row), &unit)| {
+                let mut cells = vec![input.clone()];
+                cells.extend(row.iter().map(|&e| short(in_unit(e, unit).point)));
+                cells.push(UNITS[unit].to_owned());
+                cells
+            })
+            .collect();
+        write_markdown_table(out, &thrpt_header, &rows)?;
+
+        let Some(basis) = normalize_by else {
+            return Ok(());
+        };
+        writeln!(out)?;
+        let normalized: Vec<Vec<f64>> = self
+            .rows
+            .iter()
+            .map(|row| row.iter().map(|e| e.point / row[basis].point).collect())
+            .collect();
+        let mut rows: Vec<Vec<String>> = self
+            .inputs
+            .iter()
+            .zip(&normalized)
+            .map(|(input, row)| {
+                let mut cells = vec![input.clone()];
+                cells.extend(row.iter().map(|&ratio| short(ratio)));
+                cells
+            })
+            .collect();
+        let mut geometric_mean = vec!["geometric mean".to_owned()];
+        geometric_mean.extend((0..self.implementations.len()).map(|column| {
+            let log_sum: f64 = normalized.iter().map(|row| row[column].ln()).sum();
+            short((log_sum / normalized.len() as f64).exp())
+        }));
+        rows.push(geometric_mean);
+        write_markdown_table(out, &header, &rows)
+    }
+
+    /// The legend of [Table::write_markdown], and the ids it gives the implementations
+    fn write_legend(&self, out: &mut impl Write) -> io::Result<Vec<String>> {
+        let ids: Vec<String> = (1..=self.implementations.len())
+            .map(|id| id.to_string())
+            .collect();
+        let legend: Vec<Vec<String>> = ids
+            .iter()
+            .zip(&self.implementations)
+            .map(|(id, implementation)| {
+                vec![id.clone(), format!("`{}`", implementation.replace(' ', ""))]
+            })
+            .collect();
+        write_markdown_table(
+            out,
+            &["id #".to_owned(), "implementation".to_owned()],
+            &legend,
+        )?;
+        writeln!(out)?;
+        Ok(ids)
+    }
+
+    /// Like [Table::write_markdown] without normalizing, but each cell also has the half-width of the range
+    /// around its value (relative to it), for tables read with [read_group_spread](crate::criterion::read_group_spread),
+    /// like `412.3 ±3.1%`.
+    pub fn write_spread_markdown(&self, out: &mut impl Write) -> io::Result<()> {
+        let ids = self.write_legend(out)?;
+        let mut header = vec!["input_name \\ impl name".to_owned()];
+        header.extend(ids);
+        header.push("thrpt unit".to_owned());
+        let units = self.units(false);
+        let rows: Vec<Vec<String>> = self
+            .inputs
+            .iter()
+            .zip(&self.rows)
+            .zip(&units)
+            .map(|((input, row), &unit)| {
+                let mut cells = vec![input.clone()];
+                cells.extend(row.iter().map(|&e| {
+                    let spread = (e.upper - e.lower) / 2.0 / e.point;
+                    format!("{} ±{:.1}%", short(in_unit(e, unit).point), spread * 100.0)
+                }));
+                cells.push(UNITS[unit].to_owned());
+                cells
+            })
+            .collect();
+        write_markdown_table(out, &header, &rows)
+    }
+}
+
+/// With every column padded to its widest cell
+pub(crate) fn write_markdown_table(
+    out: &mut impl Write,
+    header: &[String],
+    rows: &[Vec<String>],
+) -> io::Result<()> {
+    let widths: Vec<usize> = (0..header.len())
+        .map(|column| {
+            rows.iter()
+                .map(|row| row[column].chars().count())
+                .chain([header[column].chars().count()])
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    let write_row = |out: &mut dyn Write, cells: &[String]| -> io::Result<()> {
+        for (cell, &width) in cells.iter().zip(&widths) {
+            write!(out, "| {cell:width$} ")?;
+        }
+        writeln!(out, "|")
+    };
+    write_row(out, header)?;
+    for &width in &widths {
+        write!(out, "|{}", "-".repeat(width + 2))?;
+    }
+    writeln!(out, "|")?;
+    for row in rows {
+        write_row(out, row)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,78 @@
+use bench_report::{
+    compare::{Comparison, Report, Thresholds, Verdict},
+    criterion::{Benchmark, Estimate},
+};
+
+/// `ns` per iteration, `±spread` (a fraction)
+fn time(ns: f64, spread: f64) -> Estimate {
+    Estimate {
+        lower: ns * (1.0 - spread),
+        point: ns,
+        upper: ns * (1.0 + spread),
+    }
+}
+
+#[test]
+fn changes_need_to_be_significant_and_beyond_the_noise() {
+    let verdict =
+        |current| Comparison::new(String::new(), time(100.0, 0.01), current, 0.02).verdict;
+    assert_eq!(verdict(time(110.0, 0.01)), Verdict::Regressed);
+    assert_eq!(verdict(time(90.0, 0.01)), Verdict::Improved);
+    // the intervals overlap
+    assert_eq!(verdict(time(110.0, 0.2)), Verdict::Unchanged);
+    // significant, but within the threshold
+    assert_eq!(verdict(time(101.5, 0.001)), Verdict::Noise);
+}
+
+#[test]
+fn reports_each_benchmark_against_its_threshold() {
+    let benchmark = |function: &str, value: &str, ns| Benchmark {
+        function: function.to_owned(),
+        value: value.to_owned(),
+        time: time(ns, 0.01),
+        bytes: None,
+    };
+    let baseline = [
+        benchmark("logos_lex", "sample", 100.0),
+        benchmark("naive :: pooled", "sample", 100.0),
+        benchmark("naive :: pooled", "10kB number", 100.0),
+        benchmark("manual_lex", "sample", 100.0),
+    ];
+    let current = [
+        benchmark("logos_lex", "sample", 110.0),
+        benchmark("naive :: pooled", "sample", 110.0),
+        benchmark("naive :: pooled", "10kB number", 110.0),
+        benchmark("manual_lex", "long", 100.0),
+    ];
+    let mut thresholds = Thresholds::default();
+    thresholds.set("naive".to_owned(), 0.2);
+    thresholds.set("10kB".to_owned(), 0.05);
+    assert_eq!(thresholds.of("logos_lex/sample"), 0.02);
+
+    let report = Report::new(&baseline, &current, &thresholds);
+    let verdicts: Vec<_> = report
+        .comparisons
+        .iter()
+        .map(|c| (c.id.as_str(), c.threshold, c.verdict))
+        .collect();
+    assert_eq!(
+        verdicts,
+        [
+            ("logos_lex/sample", 0.02, Verdict::Regressed),
+            ("naive :: pooled/sample", 0.2, Verdict::Noise),
+            ("naive :: pooled/10kB number", 0.05, Verdict::Regressed),
+        ]
+    );
+    assert_eq!(report.regressions(), 2);
+    assert_eq!(report.only_in_baseline, ["manual_lex/sample"]);
+    assert_eq!(report.only_in_current, ["manual_lex/long"]);
+
+    let mut text = Vec::new();
+    report.write(&mut text, "before", "new").unwrap();
+    let text = String::from_utf8(text).unwrap();
+    assert!(text.starts_with("| benchmark "));
+    assert!(text.contains("| logos_lex/sample "));
+    assert!(text.contains("| 100.0 ns ±1.0% "));
+    assert!(text.contains("only in `before`: manual_lex/sample"));
+    assert!(text.ends_with("2 regressed, 0 improved, 1 within noise, 0 unchanged\n"));
+}
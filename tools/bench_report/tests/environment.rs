@@ -0,0 +1,92 @@
+use bench_report::environment::{profile_settings, Environment};
+use std::{collections::BTreeMap, fs, path::Path};
+
+#[test]
+fn reads_the_bench_and_release_profiles() {
+    let manifest = r#"
+[package]
+name = "day13_compare"
+version = "0.1.0"
+
+[profile.release]
+lto = "fat" # across crates
+codegen-units = 1
+
+[profile.dev]
+opt-level = 1
+
+[profile.bench]
+debug = true
+"#;
+    assert_eq!(
+        profile_settings(manifest),
+        [
+            r#"release.lto="fat""#,
+            "release.codegen-units=1",
+            "bench.debug=true",
+        ]
+    );
+    assert!(profile_settings("[package]\nname = \"x\"\n").is_empty());
+}
+
+#[test]
+fn saves_loads_and_writes_the_environment() {
+    let environment = Environment {
+        cpu: "Some CPU".to_owned(),
+        cores: 8,
+        simd: vec!["sse2".to_owned(), "avx2".to_owned()],
+        rustc: "rustc 1.72.0".to_owned(),
+        llvm: "16.0.5".to_owned(),
+        target: "x86_64-unknown-linux-gnu".to_owned(),
+        profile: Vec::new(),
+        variables: BTreeMap::from([("RUSTFLAGS".to_owned(), "-C target-cpu=native".to_owned())]),
+        environment_bytes: 1234,
+    };
+
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("environment");
+    let _ = fs::remove_dir_all(&dir);
+    assert_eq!(Environment::load(&dir, "new"), Ok(None));
+    environment.save(&dir, "new").unwrap();
+    assert_eq!(
+        Environment::load(&dir, "new"),
+        Ok(Some(environment.clone()))
+    );
+    // each baseline has its own
+    assert_eq!(Environment::load(&dir, "main"), Ok(None));
+    let other = Environment {
+        cores: 4,
+        ..environment.clone()
+    };
+    other.save(&dir, "main").unwrap();
+    assert_eq!(Environment::load(&dir, "main"), Ok(Some(other)));
+    assert_eq!(
+        Environment::load(&dir, "new"),
+        Ok(Some(environment.clone()))
+    );
+    fs::write(Environment::path(&dir, "new"), "{").unwrap();
+    assert!(Environment::load(&dir, "new").is_err());
+
+    let mut text = Vec::new();
+    environment.write_text(&mut text, "# ").unwrap();
+    let expected = "\
+# summary = Some CPU, 8 cores, rustc 1.72.0, x86_64-unknown-linux-gnu, RUSTFLAGS=-C target-cpu=native
+# cpu = Some CPU
+# cores = 8
+# simd = sse2, avx2
+# rustc = rustc 1.72.0
+# llvm = 16.0.5
+# target = x86_64-unknown-linux-gnu
+# profile = default
+# environment bytes = 1234
+# env RUSTFLAGS = -C target-cpu=native
+";
+    assert_eq!(String::from_utf8(text).unwrap(), expected);
+
+    // the same, but the summary
+    let mut markdown = Vec::new();
+    environment.write_markdown(&mut markdown).unwrap();
+    let markdown = String::from_utf8(markdown).unwrap();
+    assert!(markdown.starts_with("| environment "));
+    assert_eq!(markdown.lines().count(), 2 + 9);
+    assert!(!markdown.contains("summary"));
+}
@@ -0,0 +1,131 @@
+use bench_report::{
+    criterion::{Benchmark, Estimate},
+    environment::Environment,
+    history::{self, Run, Trend},
+};
+use std::{fs, path::Path};
+
+fn benchmark(function: &str, value: &str, ns: f64) -> Benchmark {
+    Benchmark {
+        function: function.to_owned(),
+        value: value.to_owned(),
+        time: Estimate {
+            lower: ns,
+            point: ns,
+            upper: ns,
+        },
+        bytes: Some(1 << 20),
+    }
+}
+
+#[test]
+fn runs_append_to_the_history_and_trend_per_input() {
+    let path = Path::new(env!("CARGO_TARGET_TMPDIR")).join("history.jsonl");
+    let _ = fs::remove_file(&path);
+
+    let first = [
+        benchmark("logos_lex", "sample", 1e9),
+        benchmark("naive :: pooled", "sample", 2e9),
+        benchmark("logos_lex", "long", 1e9),
+    ];
+    // naive :: pooled stopped running, manual_lex started
+    let second = [
+        benchmark("logos_lex", "sample", 0.5e9),
+        benchmark("manual_lex", "sample", 0.25e9),
+    ];
+    let environment = Environment {
+        cpu: "Some CPU".to_owned(),
+        cores: 8,
+        rustc: "rustc 1.72.0".to_owned(),
+        target: "x86_64-unknown-linux-gnu".to_owned(),
+        ..Environment::default()
+    };
+    history::append(&path, "1111111", 1_697_500_000, None, "Day13_A", &first).unwrap();
+    history::append(
+        &path,
+        "2222222-dirty",
+        1_697_600_000,
+        Some(&environment),
+        "Day13_A",
+        &second,
+    )
+    .unwrap();
+    history::append(
+        &path,
+        "2222222-dirty",
+        1_697_600_000,
+        Some(&environment),
+        "Sweep_depth",
+        &first,
+    )
+    .unwrap();
+
+    let entries = history::read(&path).unwrap();
+    assert_eq!(entries.len(), 8);
+    assert_eq!(entries[3].benchmark, second[0]);
+    assert_eq!(entries[0].environment, None);
+    assert_eq!(entries[3].environment.as_ref(), Some(&environment));
+
+    let trends = Trend::of_group(&entries, "Day13_A");
+    assert_eq!(trends.len(), 2);
+    let sample = &trends[0];
+    assert_eq!(sample.input, "sample");
+    assert_eq!(
+        sample.runs,
+        [
+            Run {
+                commit: "1111111".to_owned(),
+                timestamp: 1_697_500_000,
+                environment: None,
+            },
+            Run {
+                commit: "2222222-dirty".to_owned(),
+                timestamp: 1_697_600_000,
+                environment: Some(
+                    "Some CPU, 8 cores, rustc 1.72.0, x86_64-unknown-linux-gnu".to_owned()
+                ),
+            },
+        ]
+    );
+    assert_eq!(
+        sample.implementations,
+        ["logos_lex", "naive :: pooled", "manual_lex"]
+    );
+    let points: Vec<Vec<Option<f64>>> = sample
+        .rows
+        .iter()
+        .map(|row| row.iter().map(|e| e.map(|e| e.point)).collect())
+        .collect();
+    let mib = f64::from(1 << 20);
+    assert_eq!(
+        points,
+        [
+            vec![Some(mib), Some(mib / 2.0), None],
+            vec![Some(mib * 2.0), None, Some(mib * 4.0)],
+        ]
+    );
+    // `long` only ran in the first run
+    assert_eq!(trends[1].runs.len(), 1);
+
+    // one unit for the whole table, that of its lowest value
+    let mut tsv = Vec::new();
+    sample.write_tsv(&mut tsv).unwrap();
+    let tsv = String::from_utf8(tsv).unwrap();
+    let lines: Vec<&str> = tsv.lines().collect();
+    assert_eq!(
+        lines[0],
+        "KiB/s\tlow\tlogos_lex\thigh\tlow\tnaive :: pooled\thigh\tlow\tmanual_lex\thigh"
+    );
+    assert_eq!(
+        lines[1],
+        "1111111 2023-10-16 23:46\t1024\t1024\t1024\t512\t512\t512\tNaN\tNaN\tNaN"
+    );
+    // the environment of each run that recorded one, after the rows
+    assert_eq!(
+        lines.last().unwrap(),
+        &"# 2222222-dirty 2023-10-18 03:33 = Some CPU, 8 cores, rustc 1.72.0, x86_64-unknown-linux-gnu"
+    );
+    assert_eq!(sample.file_name(), "sample.data");
+
+    assert!(history::read(&path.with_extension("missing")).is_err());
+}
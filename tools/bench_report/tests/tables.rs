@@ -0,0 +1,233 @@
+use bench_report::{
+    criterion::{read_group, read_group_spread, Benchmark, Estimate},
+    table::Table,
+};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+/// `ns` per iteration, with a ±1% interval
+fn time(ns: f64) -> Estimate {
+    Estimate {
+        lower: ns * 0.99,
+        point: ns,
+        upper: ns * 1.01,
+    }
+}
+
+fn benchmark(function: &str, value: &str, ns: f64, bytes: u64) -> Benchmark {
+    Benchmark {
+        function: function.to_owned(),
+        value: value.to_owned(),
+        time: time(ns),
+        bytes: Some(bytes),
+    }
+}
+
+/// Writes the files criterion would for `benchmark`, saved `order` seconds into the run
+fn save(dir: &Path, group: &str, benchmark: &Benchmark, slope: bool, order: u64) {
+    let dir = dir
+        .join(group)
+        .join(benchmark.function.replace("::", "__"))
+        .join(&benchmark.value)
+        .join("new");
+    fs::create_dir_all(&dir).unwrap();
+    let estimate = |e: Estimate| {
+        format!(
+            r#"{{"confidence_interval":{{"confidence_level":0.95,"lower_bound":{},"upper_bound":{}}},"point_estimate":{},"standard_error":1.0}}"#,
+            e.lower, e.upper, e.point
+        )
+    };
+    // a mean that's off, to check the slope wins when there is one
+    let mean = if slope {
+        estimate(time(1.0))
+    } else {
+        estimate(benchmark.time)
+    };
+    let slope = if slope {
+        estimate(benchmark.time)
+    } else {
+        "null".to_owned()
+    };
+    fs::write(
+        dir.join("estimates.json"),
+        format!(r#"{{"mean":{mean},"median":{mean},"slope":{slope}}}"#),
+    )
+    .unwrap();
+    // samples whose time per iteration spreads exactly over the interval, with the point as their median
+    let Estimate {
+        lower,
+        point,
+        upper,
+    } = benchmark.time;
+    fs::write(
+        dir.join("sample.json"),
+        format!(
+            r#"{{"sampling_mode":"Linear","iters":[1.0,2.0,4.0,8.0],"times":[{},{},{},{}]}}"#,
+            upper,
+            lower * 2.0,
+            point * 4.0,
+            point * 8.0
+        ),
+    )
+    .unwrap();
+    let info = dir.join("benchmark.json");
+    fs::write(
+        &info,
+        format!(
+            r#"{{"group_id":"{group}","function_id":"{}","value_str":"{}","throughput":{{"Bytes":{}}}}}"#,
+            benchmark.function,
+            benchmark.value,
+            benchmark.bytes.unwrap()
+        ),
+    )
+    .unwrap();
+    fs::File::options()
+        .write(true)
+        .open(info)
+        .unwrap()
+        .set_modified(SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000 + order))
+        .unwrap();
+}
+
+fn fresh_dir(name: &str) -> PathBuf {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn reads_a_group_in_the_order_it_ran() {
+    let dir = fresh_dir("reads_a_group");
+    // run in the opposite of alphabetical order
+    let benchmarks = [
+        benchmark("naive :: pooled", "sample", 2000.0, 1024),
+        benchmark("naive :: pooled", "long", 4000.0, 4096),
+        benchmark("logos_lex", "sample", 1000.0, 1024),
+        benchmark("logos_lex", "long", 1000.0, 4096),
+    ];
+    for (order, benchmark) in benchmarks.iter().enumerate() {
+        save(&dir, "Day13_A", benchmark, order % 2 == 0, order as u64);
+    }
+    save(&dir, "Cold_A", &benchmarks[0], false, 10);
+    fs::create_dir_all(dir.join("Day13_A/report")).unwrap();
+
+    let read = read_group(&dir, "Day13_A", "new").unwrap();
+    assert_eq!(read, benchmarks);
+    assert_eq!(
+        read_group_spread(&dir, "Day13_A", "new").unwrap(),
+        benchmarks
+    );
+    assert!(read_group(&dir, "Day13_A", "some_baseline").is_err());
+
+    let throughput = read[0].throughput().unwrap();
+    assert_eq!(throughput.point, 1024.0 * 1e9 / 2000.0);
+    assert!(throughput.lower < throughput.point && throughput.point < throughput.upper);
+}
+
+/// The rows of `tsv` as their first field and the numbers after it
+fn parse_tsv(tsv: &[u8]) -> Vec<(String, Vec<f64>)> {
+    std::str::from_utf8(tsv)
+        .unwrap()
+        .lines()
+        .skip(1)
+        .map(|line| {
+            let mut fields = line.split('\t');
+            let name = fields.next().unwrap().to_owned();
+            (name, fields.map(|field| field.parse().unwrap()).collect())
+        })
+        .collect()
+}
+
+/// low, mid and high of each value, given the ±1% time interval
+fn thrpt_row(values: &[f64]) -> Vec<f64> {
+    values
+        .iter()
+        .flat_map(|&v| [v / 1.01, v, v / 0.99])
+        .collect()
+}
+
+fn assert_close(actual: &[f64], expected: &[f64]) {
+    assert_eq!(actual.len(), expected.len());
+    for (a, e) in actual.iter().zip(expected) {
+        assert!((a - e).abs() <= e * 1e-9, "{actual:?} != {expected:?}");
+    }
+}
+
+#[test]
+fn tsv_uses_the_gnuplot_scripts_format() {
+    // 1 GiB/s and 512 MiB/s, then 2 KiB/s and 4 KiB/s
+    let benchmarks = [
+        benchmark("a", "fast", 1e9, 1 << 30),
+        benchmark("b", "fast", 2e9, 1 << 30),
+        benchmark("a", "slow", 0.5e9, 1 << 10),
+        benchmark("b", "slow", 0.25e9, 1 << 10),
+    ];
+    let table = Table::throughput(&benchmarks).unwrap();
+
+    let mut tsv = Vec::new();
+    table.write_tsv(&mut tsv, false).unwrap();
+    assert!(tsv.starts_with(b"Title\tlow\ta\thigh\tlow\tb\thigh\n"));
+    // each row in the unit of its lowest value, the low end of 1 GiB/s being just under it
+    let rows = parse_tsv(&tsv);
+    assert_eq!(rows[0].0, "fast (MiB/s)");
+    assert_close(&rows[0].1, &thrpt_row(&[1024.0, 512.0]));
+    assert_eq!(rows[1].0, "slow (KiB/s)");
+    assert_close(&rows[1].1, &thrpt_row(&[2.0, 4.0]));
+
+    let mut tsv = Vec::new();
+    table.write_tsv(&mut tsv, true).unwrap();
+    assert!(tsv.starts_with(b"KiB/s\tlow\ta\thigh\tlow\tb\thigh\n"));
+    let rows = parse_tsv(&tsv);
+    assert_eq!(rows[0].0, "fast");
+    assert_close(&rows[0].1, &thrpt_row(&[1024.0 * 1024.0, 512.0 * 1024.0]));
+    assert_eq!(rows[1].0, "slow");
+    assert_close(&rows[1].1, &thrpt_row(&[2.0, 4.0]));
+
+    assert!(Table::throughput(&benchmarks[..3]).is_err());
+}
+
+#[test]
+fn markdown_numbers_and_normalizes_the_implementations() {
+    let benchmarks = [
+        benchmark("naive :: pooled", "x", 1e9, 100 << 20),
+        benchmark("naive :: no_pool", "x", 1e9, 50 << 20),
+        benchmark("naive :: pooled", "y", 1e9, 400 << 20),
+        benchmark("naive :: no_pool", "y", 1e9, 50 << 20),
+    ];
+    let table = Table::throughput(&benchmarks).unwrap();
+    let mut markdown = Vec::new();
+    table.write_markdown(&mut markdown, Some(1)).unwrap();
+    let expected = "\
+| id # | implementation   |
+|------|------------------|
+| 1    | `naive::pooled`  |
+| 2    | `naive::no_pool` |
+
+| input_name \\ impl name | 1     | 2     | thrpt unit |
+|------------------------|-------|-------|------------|
+| x                      | 100.0 | 50.00 | MiB/s      |
+| y                      | 400.0 | 50.00 | MiB/s      |
+
+| input_name \\ impl name | 1     | 2     |
+|------------------------|-------|-------|
+| x                      | 2.000 | 1.000 |
+| y                      | 8.000 | 1.000 |
+| geometric mean         | 4.000 | 1.000 |
+";
+    assert_eq!(String::from_utf8(markdown).unwrap(), expected);
+
+    let mut markdown = Vec::new();
+    table.write_spread_markdown(&mut markdown).unwrap();
+    let markdown = String::from_utf8(markdown).unwrap();
+    // the ±1% time interval
+    assert!(markdown.ends_with(
+        "\
+| x                      | 100.0 ±1.0% | 50.00 ±1.0% | MiB/s      |
+| y                      | 400.0 ±1.0% | 50.00 ±1.0% | MiB/s      |
+"
+    ));
+}